@@ -9,7 +9,7 @@ extern crate num;
 extern crate num_bigint;
 use num_bigint::{BigInt, ToBigInt};
 
-use babyjubjub_rs::{utils, Point};
+use babyjubjub_rs::{utils, Fs, Point, B8};
 
 fn criterion_benchmark(c: &mut Criterion) {
     let p: Point = Point {
@@ -28,6 +28,10 @@ fn criterion_benchmark(c: &mut Criterion) {
     let q_projective = q.projective();
 
     c.bench_function("add", |b| b.iter(|| p_projective.add(&q_projective)));
+    let p_extended = p.extended();
+    let q_extended = q.extended();
+    c.bench_function("add extended", |b| b.iter(|| p_extended.add(&q_extended)));
+    c.bench_function("double extended", |b| b.iter(|| p_extended.double()));
     let r: BigInt = BigInt::parse_bytes(b"3", 10).unwrap();
     c.bench_function("mul_scalar_small", |b| b.iter(|| p.mul_scalar(&r)));
     let r: BigInt = BigInt::parse_bytes(
@@ -37,6 +41,16 @@ fn criterion_benchmark(c: &mut Criterion) {
     .unwrap();
     c.bench_function("mul_scalar", |b| b.iter(|| p.mul_scalar(&r)));
 
+    let r_fs = Fs::from_bigint(&r);
+    c.bench_function("mul_scalar B8", |b| {
+        b.iter(|| B8.mul_scalar(&r_fs.to_bigint()))
+    });
+    c.bench_function("mul_base", |b| b.iter(|| babyjubjub_rs::mul_base(&r_fs)));
+    c.bench_function("mul_base_ct", |b| {
+        b.iter(|| babyjubjub_rs::mul_base_ct(&r_fs))
+    });
+    c.bench_function("mul_scalar_ct", |b| b.iter(|| p.mul_scalar_ct(&r_fs)));
+
     c.bench_function("point compress", |b| b.iter(|| p.compress()));
     let p_comp = p.compress();
     c.bench_function("point decompress", |b| {
@@ -51,6 +65,25 @@ fn criterion_benchmark(c: &mut Criterion) {
     c.bench_function("verify", |b| {
         b.iter(|| babyjubjub_rs::verify(pk.clone(), sig.clone(), msg.clone()))
     });
+
+    let entries: Vec<_> = (0..64)
+        .map(|i| {
+            let sk = babyjubjub_rs::new_key();
+            let msg = i.to_bigint().unwrap();
+            let sig = sk.sign(msg.clone()).unwrap();
+            (sk.public(), sig, msg)
+        })
+        .collect();
+    c.bench_function("verify 64 one by one", |b| {
+        b.iter(|| {
+            entries
+                .iter()
+                .all(|(pk, sig, msg)| babyjubjub_rs::verify(pk.clone(), sig.clone(), msg.clone()))
+        })
+    });
+    c.bench_function("verify_batch 64", |b| {
+        b.iter(|| babyjubjub_rs::verify_batch(&entries))
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
@@ -0,0 +1,108 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// ECDH key agreement between PrivateKey and PublicKey. The cofactor is cleared, so the shared
+// point is always in the prime-order subgroup.
+
+use std::fmt;
+
+use ff::*;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{blh, Error, Fr, Point, PrivateKey, PublicKey, POSEIDON};
+
+const ECDH_KDF_TAG: &[u8] = b"babyjubjub-rs ecdh";
+
+impl PrivateKey {
+    /// ECDH key agreement: the shared point is (8·a)·B = (8·b)·A, computed in constant time.
+    pub fn diffie_hellman(&self, pk: &PublicKey) -> Result<SharedSecret, Error> {
+        // clear the cofactor first, PublicKey already rules out torsion but this keeps the
+        // result in the subgroup whatever the point
+        let p8 = pk.point().double().double().double();
+        let shared = p8.mul_scalar_ct(&self.scalar());
+        if shared.is_identity() {
+            return Err(Error::IdentityPoint);
+        }
+        let mut x: [u8; 32] = [0; 32];
+        let mut y: [u8; 32] = [0; 32];
+        shared.x.into_repr().write_le(&mut x[..]).unwrap();
+        shared.y.into_repr().write_le(&mut y[..]).unwrap();
+        Ok(SharedSecret { x, y })
+    }
+}
+
+/// Shared secret of PrivateKey::diffie_hellman, wiped on drop.
+pub struct SharedSecret {
+    // little-endian coordinates of the shared point
+    x: [u8; 32],
+    y: [u8; 32],
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
+    }
+}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SharedSecret(<redacted>)")
+    }
+}
+
+impl SharedSecret {
+    /// The shared point, for protocols that derive their keys from it in a circuit.
+    pub fn point(&self) -> Point {
+        let fr = |b: &[u8; 32]| {
+            let mut repr = <Fr as PrimeField>::Repr::default();
+            repr.read_le(&b[..]).unwrap();
+            Fr::from_repr(repr).unwrap()
+        };
+        Point {
+            x: fr(&self.x),
+            y: fr(&self.y),
+        }
+    }
+
+    /// 32 bytes of symmetric key material, Blake-512(tag || x || y) truncated.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut b = Zeroizing::new(ECDH_KDF_TAG.to_vec());
+        b.extend_from_slice(&self.x);
+        b.extend_from_slice(&self.y);
+        let h = Zeroizing::new(blh(&b));
+        *array_ref!(h, 0, 32)
+    }
+
+    /// Poseidon(x, y), a field element key for circuit-friendly ciphers.
+    pub fn to_field(&self) -> Result<Fr, Error> {
+        let p = self.point();
+        POSEIDON.hash(vec![p.x, p.y]).map_err(Error::HashFailure)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::new_key;
+
+    #[test]
+    fn test_diffie_hellman() {
+        let alice = new_key();
+        let bob = new_key();
+        let ab = alice.diffie_hellman(&bob.public_key()).unwrap();
+        let ba = bob.diffie_hellman(&alice.public_key()).unwrap();
+        assert_eq!(ab.point(), ba.point());
+        assert_eq!(ab.to_bytes(), ba.to_bytes());
+        assert_eq!(ab.to_field().unwrap(), ba.to_field().unwrap());
+
+        // (8·a)·B
+        let expected = bob.public().mul_scalar(&(alice.scalar_key() << 3));
+        assert_eq!(ab.point(), expected);
+        assert!(ab.point().is_in_subgroup());
+
+        let carol = new_key();
+        let ac = alice.diffie_hellman(&carol.public_key()).unwrap();
+        assert_ne!(ac.to_bytes(), ab.to_bytes());
+        assert_eq!(format!("{:?}", ac), "SharedSecret(<redacted>)");
+    }
+}
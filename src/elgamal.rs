@@ -0,0 +1,294 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// ElGamal encryption over BabyJubJub. A point M is encrypted to the key A = a·B8 as
+// (C1, C2) = (r·B8, M + r·A), and decrypted as M = C2 - a·C1. Encrypting v·B8 instead of a
+// point gives exponential ElGamal: ciphertexts add up to encryptions of the sum, and small
+// values are recovered with a baby-step giant-step search.
+
+use std::cmp::min;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use ff::Field;
+use rand::{CryptoRng, RngCore};
+
+use crate::{
+    mul_base, mul_base_ct, Error, Fr, Fs, Point, PointExtended, PrivateKey, PublicKey, B8,
+};
+
+/// Largest max accepted by decrypt_u64. The search keeps a table of ceil(sqrt(max + 1)) points,
+/// up to 2^20 (around 100 MiB), which is cached for later calls.
+pub const DECRYPT_U64_MAX_RANGE: u64 = 1 << 40;
+// giant steps are converted to affine this many at a time, sharing one field inversion
+const GIANT_STEP_BATCH: u64 = 256;
+
+lazy_static! {
+    // the largest baby-step table built so far, it serves any smaller range too
+    static ref BABY_STEPS: Mutex<Option<Arc<BabySteps>>> = Mutex::new(None);
+}
+
+// j·B8 -> j for j < n, and n·B8
+struct BabySteps {
+    n: u64,
+    table: HashMap<Point, u64>,
+    giant_step: PointExtended,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ciphertext {
+    pub c1: Point,
+    pub c2: Point,
+}
+
+impl Ciphertext {
+    /// Componentwise sum, an encryption of the sum of the plaintexts.
+    pub fn add(&self, other: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            c1: self.c1.add(&other.c1),
+            c2: self.c2.add(&other.c2),
+        }
+    }
+
+    /// Componentwise difference, an encryption of the difference of the plaintexts.
+    pub fn sub(&self, other: &Ciphertext) -> Ciphertext {
+        Ciphertext {
+            c1: self.c1.sub(&other.c1),
+            c2: self.c2.sub(&other.c2),
+        }
+    }
+}
+
+impl std::ops::Add for Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: Ciphertext) -> Ciphertext {
+        Ciphertext::add(&self, &rhs)
+    }
+}
+
+impl<'a> std::ops::Add<&'a Ciphertext> for &'a Ciphertext {
+    type Output = Ciphertext;
+
+    fn add(self, rhs: &'a Ciphertext) -> Ciphertext {
+        Ciphertext::add(self, rhs)
+    }
+}
+
+impl std::ops::Sub for Ciphertext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: Ciphertext) -> Ciphertext {
+        Ciphertext::sub(&self, &rhs)
+    }
+}
+
+impl<'a> std::ops::Sub<&'a Ciphertext> for &'a Ciphertext {
+    type Output = Ciphertext;
+
+    fn sub(self, rhs: &'a Ciphertext) -> Ciphertext {
+        Ciphertext::sub(self, rhs)
+    }
+}
+
+fn encrypt_with(pk: &PublicKey, m: &Point, r: &Fs) -> Ciphertext {
+    Ciphertext {
+        c1: mul_base_ct(r),
+        c2: m.add(&pk.point().mul_scalar_ct(r)),
+    }
+}
+
+pub fn encrypt<R: RngCore + CryptoRng>(pk: &PublicKey, m: &Point, rng: &mut R) -> Ciphertext {
+    encrypt_with(pk, m, &Fs::random(rng))
+}
+
+pub fn decrypt(sk: &PrivateKey, ct: &Ciphertext) -> Point {
+    ct.c2.sub(&ct.c1.mul_scalar_ct(&sk.scalar()))
+}
+
+/// Exponential ElGamal: encrypts v·B8.
+pub fn encrypt_u64<R: RngCore + CryptoRng>(pk: &PublicKey, v: u64, rng: &mut R) -> Ciphertext {
+    encrypt(pk, &mul_base(&Fs::from_bigint(&v.into())), rng)
+}
+
+/// Decrypts an exponential ElGamal ciphertext of a value in [0, max], in O(sqrt(max)) point
+/// operations and memory. Fails with RangeTooLarge above DECRYPT_U64_MAX_RANGE.
+pub fn decrypt_u64(sk: &PrivateKey, ct: &Ciphertext, max: u64) -> Result<u64, Error> {
+    discrete_log(&decrypt(sk, ct), max)
+}
+
+/// Re-encrypts the same plaintext with fresh randomness, unlinkable to ct.
+pub fn rerandomize<R: RngCore + CryptoRng>(
+    pk: &PublicKey,
+    ct: &Ciphertext,
+    rng: &mut R,
+) -> Ciphertext {
+    ct.add(&encrypt_with(pk, &Point::identity(), &Fs::random(rng)))
+}
+
+// Baby-step giant-step: with a table of j·B8 for j < n, n >= ceil(sqrt(max + 1)), finds
+// v = i·n + j such that m - i·(n·B8) = j·B8, for i <= max / n.
+fn discrete_log(m: &Point, max: u64) -> Result<u64, Error> {
+    if max > DECRYPT_U64_MAX_RANGE {
+        return Err(Error::RangeTooLarge);
+    }
+    let baby_steps = baby_steps(isqrt_ceil(max + 1).max(1));
+    let n = baby_steps.n;
+    let giant_step = baby_steps.giant_step.neg();
+
+    let steps = max / n + 1;
+    let mut gamma = m.extended();
+    let mut i = 0;
+    while i < steps {
+        let k = min(GIANT_STEP_BATCH, steps - i);
+        let mut batch: Vec<PointExtended> = Vec::with_capacity(k as usize);
+        for _ in 0..k {
+            batch.push(gamma);
+            gamma = gamma.add(&giant_step);
+        }
+        for (o, g) in batch_affine(&batch).iter().enumerate() {
+            if let Some(j) = baby_steps.table.get(g) {
+                let v = (i + o as u64) * n + j;
+                if v <= max {
+                    return Ok(v);
+                }
+            }
+        }
+        i += k;
+    }
+    Err(Error::DiscreteLogNotFound)
+}
+
+fn baby_steps(n: u64) -> Arc<BabySteps> {
+    let mut cached = BABY_STEPS.lock().unwrap();
+    if let Some(b) = cached.as_ref() {
+        if b.n >= n {
+            return b.clone();
+        }
+    }
+    let b8 = B8.extended();
+    let mut points: Vec<PointExtended> = Vec::with_capacity(n as usize);
+    let mut p = PointExtended::identity();
+    for _ in 0..n {
+        points.push(p);
+        p = p.add(&b8);
+    }
+    let table = batch_affine(&points)
+        .into_iter()
+        .zip(0..n)
+        .collect::<HashMap<Point, u64>>();
+    let b = Arc::new(BabySteps {
+        n,
+        table,
+        giant_step: p,
+    });
+    *cached = Some(b.clone());
+    b
+}
+
+// Affine forms of points with a single field inversion (Montgomery's trick); Z is never zero
+// on the curve.
+fn batch_affine(points: &[PointExtended]) -> Vec<Point> {
+    let mut prefix: Vec<Fr> = Vec::with_capacity(points.len());
+    let mut acc = Fr::one();
+    for p in points {
+        prefix.push(acc);
+        acc.mul_assign(&p.z);
+    }
+    let mut inv = acc.inverse().unwrap();
+    let mut affine = vec![Point::identity(); points.len()];
+    for (i, p) in points.iter().enumerate().rev() {
+        let mut z_inv = prefix[i];
+        z_inv.mul_assign(&inv);
+        inv.mul_assign(&p.z);
+        let mut x = p.x;
+        x.mul_assign(&z_inv);
+        let mut y = p.y;
+        y.mul_assign(&z_inv);
+        affine[i] = Point { x, y };
+    }
+    affine
+}
+
+// ceil(sqrt(x)), exact for all the x accepted by discrete_log
+fn isqrt_ceil(x: u64) -> u64 {
+    let mut r = (x as f64).sqrt() as u64;
+    while r * r > x {
+        r -= 1;
+    }
+    while r * r < x {
+        r += 1;
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::new_key;
+
+    #[test]
+    fn test_elgamal_point() {
+        let mut rng = rand::thread_rng();
+        let sk = new_key();
+        let pk = sk.public_key();
+        let m = new_key().public();
+
+        let ct = encrypt(&pk, &m, &mut rng);
+        assert_eq!(decrypt(&sk, &ct), m);
+
+        let ct2 = rerandomize(&pk, &ct, &mut rng);
+        assert_ne!(ct2, ct);
+        assert_eq!(decrypt(&sk, &ct2), m);
+
+        // another key does not decrypt
+        assert_ne!(decrypt(&new_key(), &ct), m);
+    }
+
+    #[test]
+    fn test_elgamal_u64() {
+        let mut rng = rand::thread_rng();
+        let sk = new_key();
+        let pk = sk.public_key();
+
+        let a = encrypt_u64(&pk, 1234, &mut rng);
+        let b = encrypt_u64(&pk, 4321, &mut rng);
+        assert_eq!(decrypt_u64(&sk, &a, 10000).unwrap(), 1234);
+        assert_eq!(decrypt_u64(&sk, &(&a + &b), 10000).unwrap(), 5555);
+        assert_eq!(decrypt_u64(&sk, &b.sub(&a), 10000).unwrap(), 3087);
+        assert_eq!(
+            decrypt_u64(&sk, &encrypt_u64(&pk, 0, &mut rng), 0).unwrap(),
+            0
+        );
+        assert_eq!(decrypt_u64(&sk, &b, 4321).unwrap(), 4321);
+
+        assert_eq!(
+            decrypt_u64(&sk, &b, 4320).unwrap_err(),
+            Error::DiscreteLogNotFound
+        );
+        assert_eq!(decrypt_u64(&sk, &(&b - &a), 10000).unwrap(), 3087);
+        assert_eq!(decrypt_u64(&sk, &(b.clone() - a), 10000).unwrap(), 3087);
+
+        assert_eq!(
+            decrypt_u64(&sk, &encrypt_u64(&pk, 1, &mut rng), u64::MAX).unwrap_err(),
+            Error::RangeTooLarge
+        );
+        // the cached table of a larger range serves smaller ones, and values up to max are found
+        let c = encrypt_u64(&pk, 1 << 20, &mut rng);
+        assert_eq!(decrypt_u64(&sk, &c, 1 << 20).unwrap(), 1 << 20);
+        assert_eq!(decrypt_u64(&sk, &c, 3 << 20).unwrap(), 1 << 20);
+        assert_eq!(decrypt_u64(&sk, &b, 4321).unwrap(), 4321);
+    }
+
+    #[test]
+    fn test_isqrt_ceil() {
+        for x in [1u64, 2, 3, 4, 5, 99, 100, 101] {
+            let r = isqrt_ceil(x);
+            assert!(r * r >= x && (r - 1) * (r - 1) < x);
+        }
+        let r = isqrt_ceil(DECRYPT_U64_MAX_RANGE + 1);
+        assert_eq!(r, (1 << 20) + 1);
+        assert_eq!(isqrt_ceil(DECRYPT_U64_MAX_RANGE), 1 << 20);
+        assert_eq!(isqrt_ceil((1 << 40) - 1), 1 << 20);
+    }
+}
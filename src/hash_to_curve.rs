@@ -0,0 +1,229 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Hashing to the curve.
+//
+// hash_to_curve follows the random-oracle construction of RFC 9380: the message is expanded with
+// expand_message_xmd into two field elements, each is mapped with Elligator 2 to the Montgomery
+// form v² = u³ + 168698·u² + u, carried to the twisted Edwards form by x = u/v, y = (u-1)/(u+1),
+// and the sum of the two points is multiplied by the cofactor 8. The RFC defines no suite for
+// BabyJubJub; this one uses BLAKE-512 in expand_message_xmd (already used for key derivation),
+// L = 48 bytes per field element and Z = 5, the smallest non-square as chosen by the RFC.
+//
+// hash_to_curve_try_and_increment hashes "<domain>_<msg>_<try>" with blake256 until the digest
+// decompresses to a point, as circomlib derives its Pedersen generators.
+
+use ff::PrimeField;
+use num_bigint::{BigInt, Sign, ToBigInt};
+use num_traits::Zero;
+
+use crate::{blh, decompress_point, utils, Fr, Point, Q};
+
+// BLAKE-512 output and block sizes, b_in_bytes and s_in_bytes of RFC 9380
+const B_IN_BYTES: usize = 64;
+const S_IN_BYTES: usize = 128;
+// ceil((ceil(log2(q)) + 128) / 8)
+const FIELD_ELEMENT_BYTES: usize = 48;
+const MAX_DST_LEN: usize = 255;
+const OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+// Montgomery coefficient J = 2·(a + d) / (a - d), with K = 4 / (a - d) = 1
+const MONTGOMERY_J: u32 = 168698;
+const ELL2_Z: u32 = 5;
+
+/// Hashes msg to a point of the prime-order subgroup, with domain as the domain separation tag.
+pub fn hash_to_curve(domain: &[u8], msg: &[u8]) -> Point {
+    let u = hash_to_field(domain, msg, 2);
+    let q0 = montgomery_to_edwards(map_to_curve_elligator2(&u[0]));
+    let q1 = montgomery_to_edwards(map_to_curve_elligator2(&u[1]));
+    q0.add(&q1).double().double().double()
+}
+
+/// Hashes msg to a point of the prime-order subgroup by try-and-increment over blake256, as
+/// circomlib's getBasePoint does for domain "PedersenGenerator" and a 32-digit point index.
+///
+/// The running time depends on the input, do not use it on secret messages.
+pub fn hash_to_curve_try_and_increment(domain: &[u8], msg: &[u8]) -> Point {
+    let mut try_idx: usize = 0;
+    loop {
+        let mut s: Vec<u8> = Vec::new();
+        s.extend_from_slice(domain);
+        s.push(b'_');
+        s.extend_from_slice(msg);
+        s.extend_from_slice(format!("_{:032}", try_idx).as_bytes());
+        let mut h: [u8; 32] = [0; 32];
+        blake::hash(256, &s, &mut h).unwrap();
+        // clear bit 254, the last bit of y for it to be a field element is bit 253
+        h[31] &= 0xBF;
+        if let Ok(p) = decompress_point(h) {
+            let p8 = p.double().double().double();
+            if !p8.is_identity() {
+                return p8;
+            }
+        }
+        try_idx += 1;
+    }
+}
+
+// expand_message_xmd of RFC 9380 section 5.3.1 over BLAKE-512. len is at most
+// 255 · B_IN_BYTES, which the callers in this module stay far below.
+fn expand_message_xmd(msg: &[u8], dst: &[u8], len: usize) -> Vec<u8> {
+    let ell = len.div_ceil(B_IN_BYTES);
+    assert!(ell <= 255 && len <= 65535);
+
+    let mut dst_prime = if dst.len() > MAX_DST_LEN {
+        blh(&utils::concatenate_arrays(OVERSIZE_DST_PREFIX, dst))
+    } else {
+        dst.to_vec()
+    };
+    dst_prime.push(dst_prime.len() as u8);
+
+    let mut msg_prime: Vec<u8> = vec![0; S_IN_BYTES];
+    msg_prime.extend_from_slice(msg);
+    msg_prime.extend_from_slice(&(len as u16).to_be_bytes());
+    msg_prime.push(0);
+    msg_prime.extend_from_slice(&dst_prime);
+    let b_0 = blh(&msg_prime);
+
+    let mut uniform_bytes: Vec<u8> = Vec::with_capacity(ell * B_IN_BYTES);
+    let mut b_i: Vec<u8> = vec![0; B_IN_BYTES];
+    for i in 1..=ell {
+        let mut input: Vec<u8> = b_0.iter().zip(b_i.iter()).map(|(a, b)| a ^ b).collect();
+        input.push(i as u8);
+        input.extend_from_slice(&dst_prime);
+        b_i = blh(&input);
+        uniform_bytes.extend_from_slice(&b_i);
+    }
+    uniform_bytes.truncate(len);
+    uniform_bytes
+}
+
+fn hash_to_field(dst: &[u8], msg: &[u8], count: usize) -> Vec<BigInt> {
+    let uniform_bytes = expand_message_xmd(msg, dst, count * FIELD_ELEMENT_BYTES);
+    uniform_bytes
+        .chunks(FIELD_ELEMENT_BYTES)
+        .map(|b| utils::modulus(&BigInt::from_bytes_be(Sign::Plus, b), &Q))
+        .collect()
+}
+
+fn is_square(a: &BigInt) -> bool {
+    a.is_zero() || utils::legendre_symbol(a, &Q) == 1
+}
+
+// the square root of a with the given parity, a must be a square
+fn sqrt_with_sign(a: &BigInt, odd: bool) -> BigInt {
+    if a.is_zero() {
+        return Zero::zero();
+    }
+    let y = utils::modsqrt(a, &Q).unwrap();
+    if (&y % 2 == 1.to_bigint().unwrap()) == odd {
+        y
+    } else {
+        &*Q - y
+    }
+}
+
+// map_to_curve_elligator2 of RFC 9380 section 6.7.1, returning Montgomery coordinates (s, t)
+fn map_to_curve_elligator2(u: &BigInt) -> (BigInt, BigInt) {
+    let j = MONTGOMERY_J.to_bigint().unwrap();
+    let g = |x: &BigInt| utils::modulus(&(x * x * x + &j * x * x + x), &Q);
+
+    let tv = utils::modulus(&(ELL2_Z.to_bigint().unwrap() * u * u + 1), &Q);
+    // x1 = -J / (1 + Z·u²), or -J when the denominator is zero
+    let x1 = match utils::modinv(&tv, &Q) {
+        Ok(inv) => utils::modulus(&(-&j * inv), &Q),
+        Err(_) => &*Q - &j,
+    };
+    let gx1 = g(&x1);
+    if is_square(&gx1) {
+        let y = sqrt_with_sign(&gx1, true);
+        return (x1, y);
+    }
+    let x2 = utils::modulus(&(-x1 - &j), &Q);
+    let y = sqrt_with_sign(&g(&x2), false);
+    (x2, y)
+}
+
+// x = s / t, y = (s - 1) / (s + 1); the exceptional points t = 0 and s = -1 map to the identity
+fn montgomery_to_edwards((s, t): (BigInt, BigInt)) -> Point {
+    let (inv_t, inv_s1) = match (utils::modinv(&t, &Q), utils::modinv(&(&s + 1), &Q)) {
+        (Ok(a), Ok(b)) => (a, b),
+        _ => return Point::identity(),
+    };
+    let x = utils::modulus(&(&s * inv_t), &Q);
+    let y = utils::modulus(&((s - 1) * inv_s1), &Q);
+    Point {
+        x: Fr::from_str(&x.to_string()).unwrap(),
+        y: Fr::from_str(&y.to_string()).unwrap(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_to_curve() {
+        // computed with an independent Python implementation of the suite
+        let p = hash_to_curve(b"babyjubjub-rs test", b"");
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x00e021ab378e43f6097fa9051da1a96c0306a73fa03b9fe2dcccdcbf84c365c5)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x1c0a57cfbd581485144399ba5050a5fd45c44632d12e86ba70176eb6a4269e31)"
+        );
+        assert!(p.is_in_subgroup());
+
+        let p = hash_to_curve(b"babyjubjub-rs test", b"abc");
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x09287e081ed04e915c75b80b8800ff632fc102ac2fc91815e4f54d09d22ea9b3)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x006db9caa656bffd38c1c9cde28617558f6607ad15e846fa6c55dd3e81917084)"
+        );
+        assert!(p.is_in_subgroup());
+
+        // domain separation
+        assert_ne!(p, hash_to_curve(b"babyjubjub-rs other", b"abc"));
+
+        // oversize tags are hashed first
+        let p = hash_to_curve(&[b'x'; 300], b"abc");
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x3055249145cad0228de809a00d6d3ed700b7f6589dc28814fc8da054810e4b81)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x091a535ae8152e13c58efb2bba871f63d3a25d64e7634676b9016754dd0eb685)"
+        );
+    }
+
+    #[test]
+    fn test_elligator2_on_curve() {
+        for i in 0..16 {
+            let u = i.to_bigint().unwrap();
+            let p = montgomery_to_edwards(map_to_curve_elligator2(&u));
+            assert!(p.is_on_curve());
+        }
+    }
+
+    #[test]
+    fn test_hash_to_curve_try_and_increment() {
+        // circomlib's pedersen generator 0
+        let p =
+            hash_to_curve_try_and_increment(b"PedersenGenerator", format!("{:032}", 0).as_bytes());
+        assert_eq!(p, crate::pedersen_hash::base_point(0));
+
+        let p = hash_to_curve_try_and_increment(b"babyjubjub-rs test", b"abc");
+        assert!(p.is_in_subgroup());
+        assert!(!p.is_identity());
+        assert_ne!(
+            p,
+            hash_to_curve_try_and_increment(b"babyjubjub-rs test", b"abd")
+        );
+    }
+}
@@ -10,21 +10,52 @@ pub type Fr = poseidon_rs::Fr; // alias
 
 #[macro_use]
 extern crate arrayref;
-extern crate generic_array;
 extern crate num;
 extern crate num_bigint;
 extern crate num_traits;
 extern crate blake;
-
-use std::{cmp::min, convert::TryInto};
-
-use num_bigint::{BigInt, RandBigInt, Sign, ToBigInt};
+extern crate subtle;
+extern crate tiny_keccak;
+extern crate zeroize;
+
+use std::{
+    cmp::min,
+    convert::TryInto,
+    fmt,
+    hash::{Hash, Hasher},
+};
+
+use num_bigint::{BigInt, Sign, ToBigInt};
 use num_traits::One;
-
-use generic_array::GenericArray;
-
+use rand::{CryptoRng, RngCore};
+
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
+
+pub mod ecdh;
+pub mod elgamal;
+pub mod hash_to_curve;
+pub mod message;
+pub mod mimc7;
+pub mod mimcsponge;
+pub mod msm;
+pub mod pedersen_commitment;
+pub mod pedersen_hash;
+pub mod poseidon_encryption;
+pub mod scalar;
+#[cfg(feature = "serde")]
+pub mod serialization;
 pub mod utils;
 
+pub use ecdh::SharedSecret;
+pub use hash_to_curve::{hash_to_curve, hash_to_curve_try_and_increment};
+pub use message::{bytes_to_msg, fields_to_msg, poseidon_hash_bytes};
+pub use msm::multi_scalar_mul;
+pub use pedersen_commitment::PedersenCommitment;
+pub use pedersen_hash::pedersen_hash;
+pub use poseidon_encryption::{poseidon_decrypt, poseidon_encrypt, SharedKey};
+pub use scalar::Fs;
+
 #[macro_use]
 extern crate lazy_static;
 
@@ -37,7 +68,7 @@ lazy_static! {
         b"21888242871839275222246405745257275088548364400416034343698204186575808495617",10
     )
         .unwrap();
-    static ref B8: Point = Point {
+    pub static ref B8: Point = Point {
         x: Fr::from_str(
                "5299619240641551281634865583518297030282874472190772894086521144482721001553",
            )
@@ -59,16 +90,199 @@ lazy_static! {
     )
         .unwrap()
         >> 3;
+    static ref EIGHT: Fs = Fs::from_str("8").unwrap();
     static ref POSEIDON: poseidon_rs::Poseidon = Poseidon::new();
+    static ref B8_TABLE: Vec<Vec<PointProjective>> = base_table();
+}
+
+const SCHNORR_NONCE_TAG: &[u8] = b"babyjubjub-rs schnorr nonce";
+const SCALAR_KEY_PREFIX_TAG: &[u8] = b"babyjubjub-rs scalar key prefix";
+
+// Number of bits of the scalar processed by each entry of B8_TABLE.
+const BASE_WINDOW: usize = 4;
+
+// B8_TABLE[i][j] = j·16^i·B8, so that a scalar multiplication of B8 becomes one table
+// lookup and one addition per 4-bit window, without any doubling.
+fn base_table() -> Vec<Vec<PointProjective>> {
+    let mut table = Vec::with_capacity(256 / BASE_WINDOW);
+    let mut base = B8.projective();
+    for _ in 0..256 / BASE_WINDOW {
+        let mut row = Vec::with_capacity(1 << BASE_WINDOW);
+        let mut acc = identity_projective();
+        for _ in 0..1 << BASE_WINDOW {
+            row.push(acc.affine().projective());
+            acc = acc.add(&base);
+        }
+        base = acc;
+        table.push(row);
+    }
+    table
+}
+
+fn identity_projective() -> PointProjective {
+    PointProjective {
+        x: Fr::zero(),
+        y: Fr::one(),
+        z: Fr::one(),
+    }
+}
+
+/// Computes s·B8 using a precomputed table of multiples of the base point.
+///
+/// The running time depends on the scalar, use mul_base_ct for secret scalars.
+pub fn mul_base(s: &Fs) -> Point {
+    let b = s.to_bytes_le();
+    let mut r = identity_projective();
+    for (i, row) in B8_TABLE.iter().enumerate() {
+        let digit = (b[i / 2] >> (4 * (i % 2))) & 0x0F;
+        if digit != 0 {
+            r = r.add(&row[digit as usize]);
+        }
+    }
+    r.affine()
+}
+
+/// Computes s·B8 without branches or memory accesses that depend on s: every window performs
+/// one addition, and table entries are read by scanning the whole row. The remaining timing
+/// depends on the field arithmetic of ff.
+pub fn mul_base_ct(s: &Fs) -> Point {
+    let b = Zeroizing::new(s.to_bytes_le());
+    let mut r = identity_projective();
+    for (i, row) in B8_TABLE.iter().enumerate() {
+        let digit = (b[i / 2] >> (4 * (i % 2))) & 0x0F;
+        r = r.add(&ct_lookup(row, digit));
+    }
+    r.affine()
+}
+
+// Returns table[digit] without branching or indexing on digit.
+fn ct_lookup<T: ConditionallySelectable>(table: &[T], digit: u8) -> T {
+    let mut r = table[0];
+    for (j, entry) in table.iter().enumerate().skip(1) {
+        r.conditional_assign(entry, (j as u8).ct_eq(&digit));
+    }
+    r
 }
 
-#[derive(Clone, Debug)]
+// Computes a + choice·(b - a), so the result is never decoded and re-validated from the
+// selected limbs. Timing then rests on the field arithmetic of ff, as for the point additions.
+fn fr_conditional_select(a: &Fr, b: &Fr, choice: Choice) -> Fr {
+    let c = Fr::from_repr(<Fr as PrimeField>::Repr::from(choice.unwrap_u8() as u64)).unwrap();
+    let mut r = *b;
+    r.sub_assign(a);
+    r.mul_assign(&c);
+    r.add_assign(a);
+    r
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The coordinates do not satisfy the curve equation.
+    NotOnCurve,
+    /// The point is not in the prime-order subgroup generated by B8.
+    NotInSubgroup,
+    /// A value is not below the modulus of the field it should belong to.
+    FieldOverflow,
+    /// The value is not a quadratic residue.
+    NoSquareRoot,
+    /// The value has no modular inverse (it is zero).
+    NoInverse,
+    /// The identity point is not accepted as a key or signature component.
+    IdentityPoint,
+    /// An input does not have the expected length.
+    InvalidLength { expected: usize, actual: usize },
+    /// A string is not valid hex.
+    InvalidHex,
+    /// A ciphertext failed authentication.
+    InvalidCiphertext,
+    /// The discrete logarithm is outside the searched range.
+    DiscreteLogNotFound,
+    /// The discrete logarithm search range is above the supported bound.
+    RangeTooLarge,
+    /// The private key was built from a scalar and has no seed.
+    ScalarKey,
+    /// The key derivation parameters are out of range.
+    InvalidKdfParams,
+    /// The Poseidon hash rejected its inputs.
+    HashFailure(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NotOnCurve => write!(f, "point is not on the curve"),
+            Error::NotInSubgroup => write!(f, "point is not in the prime-order subgroup"),
+            Error::FieldOverflow => write!(f, "value outside the finite field"),
+            Error::NoSquareRoot => write!(f, "not a mod p square"),
+            Error::NoInverse => write!(f, "no mod inv of zero"),
+            Error::IdentityPoint => write!(f, "point is the identity"),
+            Error::InvalidLength { expected, actual } => {
+                write!(f, "invalid length: expected {}, got {}", expected, actual)
+            }
+            Error::InvalidHex => write!(f, "invalid hex string"),
+            Error::InvalidCiphertext => write!(f, "ciphertext authentication failed"),
+            Error::DiscreteLogNotFound => write!(f, "discrete log not found in range"),
+            Error::RangeTooLarge => write!(f, "discrete log search range too large"),
+            Error::ScalarKey => write!(f, "key built from a scalar has no seed"),
+            Error::InvalidKdfParams => write!(f, "invalid key derivation parameters"),
+            Error::HashFailure(e) => write!(f, "hash failure: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[derive(Clone, Copy, Debug)]
 pub struct PointProjective {
     pub x: Fr,
     pub y: Fr,
     pub z: Fr,
 }
 
+// Projective points are equal when X1·Z2 = X2·Z1 and Y1·Z2 = Y2·Z1, no inversion needed.
+fn projective_eq(x1: &Fr, y1: &Fr, z1: &Fr, x2: &Fr, y2: &Fr, z2: &Fr) -> bool {
+    let mut lhs = *x1;
+    lhs.mul_assign(z2);
+    let mut rhs = *x2;
+    rhs.mul_assign(z1);
+    if lhs != rhs {
+        return false;
+    }
+    let mut lhs = *y1;
+    lhs.mul_assign(z2);
+    let mut rhs = *y2;
+    rhs.mul_assign(z1);
+    lhs == rhs
+}
+
+fn hash_fr<H: Hasher>(e: &Fr, state: &mut H) {
+    e.into_repr().as_ref().hash(state);
+}
+
+impl PartialEq for PointProjective {
+    fn eq(&self, other: &Self) -> bool {
+        projective_eq(&self.x, &self.y, &self.z, &other.x, &other.y, &other.z)
+    }
+}
+
+impl Eq for PointProjective {}
+
+impl Hash for PointProjective {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.affine().hash(state);
+    }
+}
+
+impl ConditionallySelectable for PointProjective {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        PointProjective {
+            x: fr_conditional_select(&a.x, &b.x, choice),
+            y: fr_conditional_select(&a.y, &b.y, choice),
+            z: fr_conditional_select(&a.z, &b.z, choice),
+        }
+    }
+}
+
 impl PointProjective {
     pub fn affine(&self) -> Point {
         if self.z.is_zero() {
@@ -132,15 +346,277 @@ impl PointProjective {
             z: z3,
         }
     }
+
+    pub fn extended(&self) -> PointExtended {
+        // (X:Y:Z) -> (XZ:YZ:XY:Z^2)
+        let mut x = self.x;
+        x.mul_assign(&self.z);
+        let mut y = self.y;
+        y.mul_assign(&self.z);
+        let mut t = self.x;
+        t.mul_assign(&self.y);
+        let mut z = self.z;
+        z.square();
+        PointExtended { x, y, t, z }
+    }
+}
+
+// Extended twisted Edwards coordinates (X:Y:T:Z), with x = X/Z, y = Y/Z and T = XY/Z.
+#[derive(Clone, Copy, Debug)]
+pub struct PointExtended {
+    pub x: Fr,
+    pub y: Fr,
+    pub t: Fr,
+    pub z: Fr,
+}
+
+impl PartialEq for PointExtended {
+    fn eq(&self, other: &Self) -> bool {
+        projective_eq(&self.x, &self.y, &self.z, &other.x, &other.y, &other.z)
+    }
+}
+
+impl Eq for PointExtended {}
+
+impl Hash for PointExtended {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.affine().hash(state);
+    }
+}
+
+impl ConditionallySelectable for PointExtended {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        PointExtended {
+            x: fr_conditional_select(&a.x, &b.x, choice),
+            y: fr_conditional_select(&a.y, &b.y, choice),
+            t: fr_conditional_select(&a.t, &b.t, choice),
+            z: fr_conditional_select(&a.z, &b.z, choice),
+        }
+    }
+}
+
+impl PointExtended {
+    pub fn identity() -> PointExtended {
+        PointExtended {
+            x: Fr::zero(),
+            y: Fr::one(),
+            t: Fr::zero(),
+            z: Fr::one(),
+        }
+    }
+
+    pub fn affine(&self) -> Point {
+        self.projective().affine()
+    }
+
+    pub fn neg(&self) -> PointExtended {
+        // -(x, y) = (-x, y)
+        let mut x = self.x;
+        x.negate();
+        let mut t = self.t;
+        t.negate();
+        PointExtended {
+            x,
+            y: self.y,
+            t,
+            z: self.z,
+        }
+    }
+
+    pub fn projective(&self) -> PointProjective {
+        PointProjective {
+            x: self.x,
+            y: self.y,
+            z: self.z,
+        }
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    pub fn add(&self, q: &PointExtended) -> PointExtended {
+        // add-2008-hwcd https://hyperelliptic.org/EFD/g1p/auto-twisted-extended.html#addition-add-2008-hwcd
+        let mut a = self.x;
+        a.mul_assign(&q.x);
+        let mut b = self.y;
+        b.mul_assign(&q.y);
+        let mut c = *D;
+        c.mul_assign(&self.t);
+        c.mul_assign(&q.t);
+        let mut d = self.z;
+        d.mul_assign(&q.z);
+        let mut e = self.x;
+        e.add_assign(&self.y);
+        let mut x2y2 = q.x;
+        x2y2.add_assign(&q.y);
+        e.mul_assign(&x2y2);
+        e.sub_assign(&a);
+        e.sub_assign(&b);
+        let mut f = d;
+        f.sub_assign(&c);
+        let mut g = d;
+        g.add_assign(&c);
+        let mut h = *A;
+        h.mul_assign(&a);
+        h.negate();
+        h.add_assign(&b);
+
+        let mut x3 = e;
+        x3.mul_assign(&f);
+        let mut y3 = g;
+        y3.mul_assign(&h);
+        let mut t3 = e;
+        t3.mul_assign(&h);
+        let mut z3 = f;
+        z3.mul_assign(&g);
+
+        PointExtended {
+            x: x3,
+            y: y3,
+            t: t3,
+            z: z3,
+        }
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    pub fn double(&self) -> PointExtended {
+        // dbl-2008-hwcd https://hyperelliptic.org/EFD/g1p/auto-twisted-extended.html#doubling-dbl-2008-hwcd
+        let mut a = self.x;
+        a.square();
+        let mut b = self.y;
+        b.square();
+        let mut c = self.z;
+        c.square();
+        c.double();
+        let mut d = *A;
+        d.mul_assign(&a);
+        let mut e = self.x;
+        e.add_assign(&self.y);
+        e.square();
+        e.sub_assign(&a);
+        e.sub_assign(&b);
+        let mut g = d;
+        g.add_assign(&b);
+        let mut f = g;
+        f.sub_assign(&c);
+        let mut h = d;
+        h.sub_assign(&b);
+
+        let mut x3 = e;
+        x3.mul_assign(&f);
+        let mut y3 = g;
+        y3.mul_assign(&h);
+        let mut t3 = e;
+        t3.mul_assign(&h);
+        let mut z3 = f;
+        z3.mul_assign(&g);
+
+        PointExtended {
+            x: x3,
+            y: y3,
+            t: t3,
+            z: z3,
+        }
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Point {
     pub x: Fr,
     pub y: Fr,
 }
 
+impl Hash for Point {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash_fr(&self.x, state);
+        hash_fr(&self.y, state);
+    }
+}
+
 impl Point {
+    /// The neutral element (0, 1).
+    pub fn identity() -> Point {
+        Point {
+            x: Fr::zero(),
+            y: Fr::one(),
+        }
+    }
+
+    pub fn is_identity(&self) -> bool {
+        self.x.is_zero() && self.y == Fr::one()
+    }
+
+    pub fn neg(&self) -> Point {
+        let mut x = self.x;
+        x.negate();
+        Point { x, y: self.y }
+    }
+
+    pub fn add(&self, p: &Point) -> Point {
+        self.extended().add(&p.extended()).affine()
+    }
+
+    pub fn sub(&self, p: &Point) -> Point {
+        self.extended().add(&p.extended().neg()).affine()
+    }
+
+    pub fn double(&self) -> Point {
+        self.extended().double().affine()
+    }
+
+    /// Builds a point from its coordinates, checking that it lies on the curve and in the
+    /// prime-order subgroup.
+    pub fn new(x: Fr, y: Fr) -> Result<Point, Error> {
+        let p = Point { x, y };
+        p.validate()?;
+        Ok(p)
+    }
+
+    /// Checks the twisted Edwards equation A·x² + y² = 1 + D·x²·y².
+    pub fn is_on_curve(&self) -> bool {
+        let mut x2 = self.x;
+        x2.square();
+        let mut y2 = self.y;
+        y2.square();
+
+        let mut lhs = *A;
+        lhs.mul_assign(&x2);
+        lhs.add_assign(&y2);
+
+        let mut rhs = *D;
+        rhs.mul_assign(&x2);
+        rhs.mul_assign(&y2);
+        rhs.add_assign(&Fr::one());
+
+        lhs == rhs
+    }
+
+    /// Checks that the point is on the curve and SUBORDER·P is the identity.
+    pub fn is_in_subgroup(&self) -> bool {
+        if !self.is_on_curve() {
+            return false;
+        }
+        self.mul_scalar(&SUBORDER).is_identity()
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if !self.is_on_curve() {
+            return Err(Error::NotOnCurve);
+        }
+        if !self.is_in_subgroup() {
+            return Err(Error::NotInSubgroup);
+        }
+        Ok(())
+    }
+
+    // Public keys and r_b8 must be non-identity points of the prime-order subgroup, otherwise
+    // torsion components make signatures malleable.
+    fn validate_for_verification(&self) -> Result<(), Error> {
+        self.validate()?;
+        if self.is_identity() {
+            return Err(Error::IdentityPoint);
+        }
+        Ok(())
+    }
+
     pub fn projective(&self) -> PointProjective {
         PointProjective {
             x: self.x,
@@ -149,19 +625,51 @@ impl Point {
         }
     }
 
-    pub fn mul_scalar(&self, n: &BigInt) -> Point {
-        let mut r: PointProjective = PointProjective {
-            x: Fr::zero(),
-            y: Fr::one(),
+    pub fn extended(&self) -> PointExtended {
+        let mut t = self.x;
+        t.mul_assign(&self.y);
+        PointExtended {
+            x: self.x,
+            y: self.y,
+            t,
             z: Fr::one(),
-        };
-        let mut exp: PointProjective = self.projective();
+        }
+    }
+
+    pub fn mul_scalar(&self, n: &BigInt) -> Point {
+        let mut r: PointExtended = PointExtended::identity();
+        let p: PointExtended = self.extended();
         let (_, b) = n.to_bytes_le();
-        for i in 0..n.bits() {
+        for i in (0..n.bits()).rev() {
+            r = r.double();
             if test_bit(&b, i.try_into().unwrap()) {
-                r = r.add(&exp);
+                r = r.add(&p);
             }
-            exp = exp.add(&exp);
+        }
+        r.affine()
+    }
+
+    /// Constant-time scalar multiplication, for secret scalars.
+    ///
+    /// Uses a fixed 4-bit window over all 256 bits of the scalar encoding, always performing
+    /// the same doublings and additions, with table lookups that scan the whole table. Like
+    /// mul_base_ct, it relies on the field arithmetic of ff for the rest.
+    pub fn mul_scalar_ct(&self, s: &Fs) -> Point {
+        const W: usize = 4;
+        let p = self.extended();
+        let mut table = [PointExtended::identity(); 1 << W];
+        for j in 1..1 << W {
+            table[j] = table[j - 1].add(&p);
+        }
+
+        let b = Zeroizing::new(s.to_bytes_le());
+        let mut r = PointExtended::identity();
+        for i in (0..256 / W).rev() {
+            for _ in 0..W {
+                r = r.double();
+            }
+            let digit = (b[i / 2] >> (4 * (i % 2))) & 0x0F;
+            r = r.add(&ct_lookup(&table, digit));
         }
         r.affine()
     }
@@ -181,10 +689,71 @@ impl Point {
     }
 
     pub fn equals(&self, p: Point) -> bool {
-        if self.x == p.x && self.y == p.y {
-            return true;
-        }
-        false
+        *self == p
+    }
+}
+
+impl std::ops::Add for Point {
+    type Output = Point;
+
+    fn add(self, rhs: Point) -> Point {
+        Point::add(&self, &rhs)
+    }
+}
+
+impl<'a> std::ops::Add<&'a Point> for &'a Point {
+    type Output = Point;
+
+    fn add(self, rhs: &'a Point) -> Point {
+        Point::add(self, rhs)
+    }
+}
+
+impl std::ops::Sub for Point {
+    type Output = Point;
+
+    fn sub(self, rhs: Point) -> Point {
+        Point::sub(&self, &rhs)
+    }
+}
+
+impl<'a> std::ops::Sub<&'a Point> for &'a Point {
+    type Output = Point;
+
+    fn sub(self, rhs: &'a Point) -> Point {
+        Point::sub(self, rhs)
+    }
+}
+
+impl std::ops::Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::neg(&self)
+    }
+}
+
+impl std::ops::Neg for &Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point::neg(self)
+    }
+}
+
+impl std::ops::Mul<Fs> for Point {
+    type Output = Point;
+
+    fn mul(self, rhs: Fs) -> Point {
+        self.mul_scalar_ct(&rhs)
+    }
+}
+
+impl<'a> std::ops::Mul<&'a Fs> for &'a Point {
+    type Output = Point;
+
+    fn mul(self, rhs: &'a Fs) -> Point {
+        self.mul_scalar_ct(rhs)
     }
 }
 
@@ -192,9 +761,9 @@ pub fn test_bit(b: &[u8], i: usize) -> bool {
     b[i / 8] & (1 << (i % 8)) != 0
 }
 
-pub fn recover_point(y: BigInt, sign: bool) -> Result<Point, String> {
+pub fn recover_point(y: BigInt, sign: bool) -> Result<Point, Error> {
     if y >= Q.clone() {
-        return Err("y outside the Finite Field over R".to_string());
+        return Err(Error::FieldOverflow);
     }
     let one: BigInt = One::one();
 
@@ -218,7 +787,7 @@ pub fn recover_point(y: BigInt, sign: bool) -> Result<Point, String> {
     Ok(Point { x: x_fr, y: y_fr })
 }
 
-pub fn decompress_point(bb: [u8; 32]) -> Result<Point, String> {
+pub fn decompress_point(bb: [u8; 32]) -> Result<Point, Error> {
     // https://tools.ietf.org/html/rfc8032#section-5.2.3
     let mut sign: bool = false;
     let mut b = bb;
@@ -231,35 +800,32 @@ pub fn decompress_point(bb: [u8; 32]) -> Result<Point, String> {
 }
 
 fn blh(b: &Vec<u8>) -> Vec<u8> {
-    let mut hash = [0; 64];
-    blake::hash(512, b, &mut hash).unwrap();
+    // also hashes secrets, do not leave a copy behind
+    let mut hash = Zeroizing::new([0; 64]);
+    blake::hash(512, b, &mut hash[..]).unwrap();
     hash.to_vec()
 }
 
 #[derive(Debug, Clone)]
 pub struct Signature {
     pub r_b8: Point,
-    pub s: BigInt,
+    pub s: Fs,
 }
 
 impl Signature {
     pub fn compress(&self) -> [u8; 64] {
         let mut b: Vec<u8> = Vec::new();
         b.append(&mut self.r_b8.compress().to_vec());
-        let (_, s_bytes) = self.s.to_bytes_le();
-        let mut s_32bytes: [u8; 32] = [0; 32];
-        let len = min(s_bytes.len(), s_32bytes.len());
-        s_32bytes[..len].copy_from_slice(&s_bytes[..len]);
-        b.append(&mut s_32bytes.to_vec());
+        b.append(&mut self.s.to_bytes_le().to_vec());
         let mut r: [u8; 64] = [0; 64];
         r[..].copy_from_slice(&b[..]);
         r
     }
 }
 
-pub fn decompress_signature(b: &[u8; 64]) -> Result<Signature, String> {
+pub fn decompress_signature(b: &[u8; 64]) -> Result<Signature, Error> {
     let r_b8_bytes: [u8; 32] = *array_ref!(b[..32], 0, 32);
-    let s: BigInt = BigInt::from_bytes_le(Sign::Plus, &b[32..]);
+    let s = Fs::from_bytes_le(&b[32..])?;
     let r_b8 = decompress_point(r_b8_bytes);
     match r_b8 {
         Result::Err(err) => Err(err),
@@ -267,29 +833,186 @@ pub fn decompress_signature(b: &[u8; 64]) -> Result<Signature, String> {
     }
 }
 
+/// A public key: a point of the prime-order subgroup other than the identity.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PublicKey(Point);
+
+impl PublicKey {
+    /// Wraps a point, checking that it is on the curve, in the subgroup and not the identity.
+    pub fn from_point(p: Point) -> Result<PublicKey, Error> {
+        p.validate_for_verification()?;
+        Ok(PublicKey(p))
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.0
+    }
+
+    pub fn compress(&self) -> [u8; 32] {
+        self.0.compress()
+    }
+
+    pub fn decompress(b: &[u8; 32]) -> Result<PublicKey, Error> {
+        PublicKey::from_point(decompress_point(*b)?)
+    }
+
+    /// Hex of the compressed key.
+    pub fn to_hex(&self) -> String {
+        use rustc_hex::ToHex;
+        self.compress()[..].to_hex()
+    }
+
+    pub fn from_hex(s: &str) -> Result<PublicKey, Error> {
+        PublicKey::decompress(&utils::decode_hex::<32>(s)?)
+    }
+
+    /// Poseidon(x, y), the field element circuits use to identify the key.
+    pub fn key_hash(&self) -> Result<Fr, Error> {
+        POSEIDON
+            .hash(vec![self.0.x, self.0.y])
+            .map_err(Error::HashFailure)
+    }
+
+    /// The low 160 bits of key_hash, big-endian.
+    pub fn address(&self) -> Result<[u8; 20], Error> {
+        let h = self.key_hash()?;
+        let mut b: [u8; 32] = [0; 32];
+        h.into_repr().write_be(&mut b[..]).unwrap();
+        Ok(*array_ref!(b, 12, 20))
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl From<PublicKey> for Point {
+    fn from(pk: PublicKey) -> Point {
+        pk.0
+    }
+}
+
 pub struct PrivateKey {
     key: [u8; 32],
+    // keys built with from_scalar have no seed, key then holds the scalar itself
+    scalar: bool,
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.key.zeroize();
+    }
+}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("PrivateKey(<redacted>)")
+    }
 }
 
 impl PrivateKey {
-    pub fn import(b: Vec<u8>) -> Result<PrivateKey, String> {
+    pub fn import(b: Vec<u8>) -> Result<PrivateKey, Error> {
+        let b = Zeroizing::new(b);
         if b.len() != 32 {
-            return Err(String::from("imported key can not be bigger than 32 bytes"));
+            return Err(Error::InvalidLength {
+                expected: 32,
+                actual: b.len(),
+            });
         }
-        let mut sk: [u8; 32] = [0; 32];
+        let mut sk = Zeroizing::new([0u8; 32]);
         sk.copy_from_slice(&b[..32]);
-        Ok(PrivateKey { key: sk })
+        Ok(PrivateKey::from_bytes(&sk))
     }
 
+    /// Generates a new key from 32 bytes of the given RNG.
+    pub fn generate<R: RngCore + CryptoRng>(rng: &mut R) -> PrivateKey {
+        let mut sk: [u8; 32] = [0; 32];
+        rng.fill_bytes(&mut sk);
+        let key = PrivateKey::from_bytes(&sk);
+        sk.zeroize();
+        key
+    }
+
+    /// Builds a key from its 32-byte seed.
+    pub fn from_bytes(b: &[u8; 32]) -> PrivateKey {
+        PrivateKey {
+            key: *b,
+            scalar: false,
+        }
+    }
+
+    /// Builds a key from the hex of its 32-byte seed, with or without 0x prefix.
+    pub fn from_hex(s: &str) -> Result<PrivateKey, Error> {
+        let mut b = utils::decode_hex::<32>(s)?;
+        let key = PrivateKey::from_bytes(&b);
+        b.zeroize();
+        Ok(key)
+    }
+
+    /// Builds a key that only exists as a secret scalar, such as the scalar_key of a key
+    /// derived elsewhere. Signatures use a nonce prefix derived from the scalar, so they differ
+    /// from the ones of the seed the scalar may have come from, but verify the same way.
+    /// Zero is rejected, as its public key would be the identity.
+    pub fn from_scalar(s: &Fs) -> Result<PrivateKey, Error> {
+        if s.is_zero() {
+            return Err(Error::IdentityPoint);
+        }
+        Ok(PrivateKey {
+            key: s.to_bytes_le(),
+            scalar: true,
+        })
+    }
+
+    /// Returns the 32-byte seed. Keys built with from_scalar have none and fail with ScalarKey,
+    /// as from_bytes would read their scalar back as a seed, a different key.
+    pub fn to_bytes(&self) -> Result<[u8; 32], Error> {
+        if self.scalar {
+            return Err(Error::ScalarKey);
+        }
+        Ok(self.key)
+    }
+
+    /// Returns the hex of to_bytes.
+    pub fn to_hex(&self) -> Result<String, Error> {
+        use rustc_hex::ToHex;
+        Ok(self.to_bytes()?[..].to_hex())
+    }
+
+    /// Whether the key was built with from_scalar.
+    pub fn is_scalar(&self) -> bool {
+        self.scalar
+    }
+
+    // Secret half of the expanded key, used to derive the signature nonces (RFC 8032 prefix).
+    fn nonce_prefix(&self) -> Zeroizing<Vec<u8>> {
+        if self.scalar {
+            let mut b = Zeroizing::new(SCALAR_KEY_PREFIX_TAG.to_vec());
+            b.extend_from_slice(&self.key);
+            let h = Zeroizing::new(blh(&b));
+            Zeroizing::new(h[..32].to_vec())
+        } else {
+            let h = Zeroizing::new(blh(&Zeroizing::new(self.key.to_vec())));
+            Zeroizing::new(h[32..64].to_vec())
+        }
+    }
+
+    /// The secret scalar as an integer, as circomlib's prv2bigint. BigInt cannot be wiped, the
+    /// crate itself works with PrivateKey::scalar instead.
     pub fn scalar_key(&self) -> BigInt {
+        if self.scalar {
+            return BigInt::from_bytes_le(Sign::Plus, &self.key);
+        }
         // not-compatible with circomlib implementation, but using Blake2b
         // let mut hasher = Blake2b::new();
         // hasher.update(sk_raw_bytes);
         // let mut h = hasher.finalize();
 
         // compatible with circomlib implementation
-        let hash: Vec<u8> = blh(&self.key.to_vec());
-        let mut h: Vec<u8> = hash[..32].to_vec();
+        // BigInt does not support zeroize, only the byte buffers are wiped
+        let hash = Zeroizing::new(blh(&Zeroizing::new(self.key.to_vec())));
+        let mut h = Zeroizing::new(hash[..32].to_vec());
 
         h[0] &= 0xF8;
         h[31] &= 0x7F;
@@ -299,114 +1022,393 @@ impl PrivateKey {
         sk >> 3
     }
 
+    // scalar_key reduced modulo SUBORDER, computed from wiped byte buffers only.
+    pub(crate) fn scalar(&self) -> Zeroizing<Fs> {
+        if self.scalar {
+            // from_scalar stores the canonical encoding
+            return Zeroizing::new(Fs::from_bytes_le(&self.key).unwrap());
+        }
+        let hash = Zeroizing::new(blh(&Zeroizing::new(self.key.to_vec())));
+        let mut h = Zeroizing::new([0u8; 32]);
+        h.copy_from_slice(&hash[..32]);
+
+        h[0] &= 0xF8;
+        h[31] &= 0x7F;
+        h[31] |= 0x40;
+
+        // h >> 3
+        for i in 0..31 {
+            h[i] = (h[i] >> 3) | (h[i + 1] << 5);
+        }
+        h[31] >>= 3;
+        Zeroizing::new(Fs::from_bytes_wide(&h[..]))
+    }
+
     pub fn public(&self) -> Point {
-        B8.mul_scalar(&self.scalar_key())
+        mul_base_ct(&self.scalar())
     }
 
-    pub fn sign(&self, msg: BigInt) -> Result<Signature, String> {
-        if msg > Q.clone() {
-            return Err("msg outside the Finite Field".to_string());
-        }
+    pub fn public_key(&self) -> PublicKey {
+        // B8 generates the subgroup, and the scalar is nonzero mod SUBORDER (from_scalar
+        // rejects zero, a seed hashing to a multiple of it is not a practical concern)
+        PublicKey(self.public())
+    }
+
+    pub fn sign(&self, msg: BigInt) -> Result<Signature, Error> {
+        let msg_fr = msg_to_fr(&msg)?;
+        self.sign_with(&msg_le32(&msg), |r_b8, a| {
+            let hm_input = vec![r_b8.x, r_b8.y, a.x, a.y, msg_fr];
+            let hm = POSEIDON.hash(hm_input).map_err(Error::HashFailure)?;
+            Ok(Fs::from_fr(&hm))
+        })
+    }
+
+    /// EdDSA-MiMC7 signature, as circomlib's eddsa.signMiMC.
+    pub fn sign_mimc7(&self, msg: BigInt) -> Result<Signature, Error> {
+        let msg_fr = msg_to_fr(&msg)?;
+        self.sign_with(&msg_le32(&msg), |r_b8, a| {
+            let hm = mimc7::multi_hash(&[r_b8.x, r_b8.y, a.x, a.y, msg_fr], &Fr::zero());
+            Ok(Fs::from_fr(&hm))
+        })
+    }
+
+    /// EdDSA-MiMCSponge signature, as circomlib's eddsa.signMiMCSponge.
+    pub fn sign_mimcsponge(&self, msg: BigInt) -> Result<Signature, Error> {
+        let msg_fr = msg_to_fr(&msg)?;
+        self.sign_with(&msg_le32(&msg), |r_b8, a| {
+            let hm = mimcsponge::multi_hash(&[r_b8.x, r_b8.y, a.x, a.y, msg_fr], &Fr::zero(), 1);
+            Ok(Fs::from_fr(&hm[0]))
+        })
+    }
+
+    /// EdDSA-Pedersen signature of a byte message, as circomlib's eddsa.signPedersen.
+    pub fn sign_pedersen(&self, msg: &[u8]) -> Result<Signature, Error> {
+        self.sign_with(msg, |r_b8, a| Ok(pedersen_hm(r_b8, a, msg)))
+    }
+
+    // EdDSA as in circomlib's eddsa.js, the variants only differ in hm = H(R8, A, msg):
+    // r = Blake512(h[32..64] || nonce_msg), R8 = r·B8, S = r + hm·(scalar_key << 3).
+    fn sign_with<F>(&self, nonce_msg: &[u8], hm: F) -> Result<Signature, Error>
+    where
+        F: FnOnce(&Point, &Point) -> Result<Fs, Error>,
+    {
         // let (_, sk_bytes) = self.key.to_bytes_le();
         // let mut hasher = Blake2b::new();
         // hasher.update(sk_bytes);
         // let mut h = hasher.finalize(); // h: hash(sk), s: h[32:64]
-        let mut h: Vec<u8> = blh(&self.key.to_vec());
-
-        let (_, msg_bytes) = msg.to_bytes_le();
-        let mut msg32: [u8; 32] = [0; 32];
-        msg32[..msg_bytes.len()].copy_from_slice(&msg_bytes[..]);
-        let msg_fr: Fr = Fr::from_str(&msg.to_string()).unwrap();
+        let prefix = self.nonce_prefix();
 
         // https://tools.ietf.org/html/rfc8032#section-5.1.6
-        let s = GenericArray::<u8, generic_array::typenum::U32>::from_mut_slice(&mut h[32..64]);
-        let r_bytes = utils::concatenate_arrays(s, &msg32);
-        let r_hashed: Vec<u8> = blh(&r_bytes);
-        let mut r = BigInt::from_bytes_le(Sign::Plus, &r_hashed[..]);
-        r = utils::modulus(&r, &SUBORDER);
-        let r_b8: Point = B8.mul_scalar(&r);
+        let r_bytes = Zeroizing::new(utils::concatenate_arrays(&prefix, nonce_msg));
+        let r_hashed = Zeroizing::new(blh(&r_bytes));
+        let r = Zeroizing::new(Fs::from_bytes_wide(&r_hashed[..]));
+        let r_b8: Point = mul_base_ct(&r);
         let a = &self.public();
 
-        let hm_input = vec![r_b8.x, r_b8.y, a.x, a.y, msg_fr];
-        let hm = POSEIDON.hash(hm_input)?;
+        let hm = hm(&r_b8, a)?;
 
-        let mut s = &self.scalar_key() << 3;
-        let hm_b = BigInt::parse_bytes(to_hex(&hm).as_bytes(), 16).unwrap();
-        s = hm_b * s;
-        s = r + s;
-        s %= &SUBORDER.clone();
+        // s = r + hm·(8·scalar), the secret factors are wiped before s is public
+        let mut s = Zeroizing::new(*self.scalar());
+        s.mul_assign(&EIGHT);
+        s.mul_assign(&hm);
+        s.add_assign(&r);
 
-        Ok(Signature { r_b8, s })
+        Ok(Signature { r_b8, s: *s })
     }
 
-    #[allow(clippy::many_single_char_names)]
-    pub fn sign_schnorr(&self, m: BigInt) -> Result<(Point, BigInt), String> {
-        // random r
-        let mut rng = rand::thread_rng();
-        let k = rng.gen_biguint(1024).to_bigint().unwrap();
+    /// Signs a vector of field elements, mapped into the field with fields_to_msg.
+    pub fn sign_fields(&self, fields: &[Fr]) -> Result<Signature, Error> {
+        let m = fields_to_msg(fields)?;
+        self.sign(BigInt::parse_bytes(to_hex(&m).as_bytes(), 16).unwrap())
+    }
+
+    /// Signs an arbitrary byte message, mapped into the field with bytes_to_msg.
+    pub fn sign_bytes(&self, msg: &[u8]) -> Result<Signature, Error> {
+        let m = bytes_to_msg(msg)?;
+        self.sign(BigInt::parse_bytes(to_hex(&m).as_bytes(), 16).unwrap())
+    }
+
+    // Deterministic Schnorr nonce, in the spirit of RFC 6979 / RFC 8032: the secret half of
+    // the key hash is hashed together with the message and optional extra entropy. The domain
+    // tag keeps it different from the EdDSA nonce for the same message, reusing it would
+    // reveal the key.
+    fn schnorr_nonce(&self, m: &BigInt, extra: &[u8]) -> Result<Zeroizing<Fs>, Error> {
+        msg_to_fr(m)?;
+        let prefix = self.nonce_prefix();
+        let (_, msg_bytes) = m.to_bytes_le();
+        let mut msg32: [u8; 32] = [0; 32];
+        msg32[..msg_bytes.len()].copy_from_slice(&msg_bytes[..]);
 
+        let mut k_bytes = Zeroizing::new(SCHNORR_NONCE_TAG.to_vec());
+        k_bytes.extend_from_slice(&prefix);
+        k_bytes.extend_from_slice(&msg32);
+        k_bytes.extend_from_slice(extra);
+        Ok(Zeroizing::new(Fs::from_bytes_wide(&Zeroizing::new(blh(
+            &k_bytes,
+        )))))
+    }
+
+    /// Schnorr signature with a nonce derived deterministically from the key and the message.
+    pub fn sign_schnorr(&self, m: BigInt) -> Result<SchnorrSignature, Error> {
+        let k = self.schnorr_nonce(&m, &[])?;
+        self.sign_schnorr_with_nonce(m, k)
+    }
+
+    /// Schnorr signature with 32 bytes from rng mixed into the deterministic nonce, so that
+    /// signatures are randomized but a faulty rng does not expose the key.
+    pub fn sign_schnorr_with_rng<R: RngCore + CryptoRng>(
+        &self,
+        m: BigInt,
+        rng: &mut R,
+    ) -> Result<SchnorrSignature, Error> {
+        let mut extra: [u8; 32] = [0; 32];
+        rng.fill_bytes(&mut extra);
+        let k = self.schnorr_nonce(&m, &extra)?;
+        self.sign_schnorr_with_nonce(m, k)
+    }
+
+    #[allow(clippy::many_single_char_names)]
+    fn sign_schnorr_with_nonce(
+        &self,
+        m: BigInt,
+        k: Zeroizing<Fs>,
+    ) -> Result<SchnorrSignature, Error> {
         // r = k·G
-        let r = B8.mul_scalar(&k);
+        let r = mul_base_ct(&k);
 
         // h = H(x, r, m)
         let pk = &self.public();
         let h = schnorr_hash(&pk, m, &r)?;
 
         // s= k+x·h
-        let sk_scalar = self.scalar_key();
-        let s = k + &sk_scalar * &h;
-        Ok((r, s))
+        let mut s = Zeroizing::new(*self.scalar());
+        s.mul_assign(&h);
+        s.add_assign(&k);
+        Ok(SchnorrSignature { r, s: *s })
     }
 }
 
-pub fn schnorr_hash(pk: &Point, msg: BigInt, c: &Point) -> Result<BigInt, String> {
-    if msg > Q.clone() {
-        return Err("msg outside the Finite Field".to_string());
+#[derive(Debug, Clone)]
+pub struct SchnorrSignature {
+    pub r: Point,
+    pub s: Fs,
+}
+
+impl SchnorrSignature {
+    pub fn compress(&self) -> [u8; 64] {
+        let mut b: [u8; 64] = [0; 64];
+        b[..32].copy_from_slice(&self.r.compress());
+        b[32..].copy_from_slice(&self.s.to_bytes_le());
+        b
     }
-    let msg_fr: Fr = Fr::from_str(&msg.to_string()).unwrap();
+}
+
+pub fn decompress_schnorr_signature(b: &[u8; 64]) -> Result<SchnorrSignature, Error> {
+    let r_bytes: [u8; 32] = *array_ref!(b[..32], 0, 32);
+    // s must be canonical (below SUBORDER), otherwise s + SUBORDER would be another
+    // encoding of the same signature
+    let s = Fs::from_bytes_le(&b[32..])?;
+    let r = decompress_point(r_bytes)?;
+    Ok(SchnorrSignature { r, s })
+}
+
+pub fn schnorr_hash(pk: &Point, msg: BigInt, c: &Point) -> Result<Fs, Error> {
+    let msg_fr = msg_to_fr(&msg)?;
     let hm_input = vec![pk.x, pk.y, c.x, c.y, msg_fr];
-    let h = POSEIDON.hash(hm_input)?;
-    let h_b = BigInt::parse_bytes(to_hex(&h).as_bytes(), 16).unwrap();
-    Ok(h_b)
+    let h = POSEIDON.hash(hm_input).map_err(Error::HashFailure)?;
+    Ok(Fs::from_fr(&h))
 }
 
-pub fn verify_schnorr(pk: Point, m: BigInt, r: Point, s: BigInt) -> Result<bool, String> {
+pub fn verify_schnorr(pk: &PublicKey, m: &BigInt, sig: &SchnorrSignature) -> Result<bool, Error> {
+    let pk = pk.point();
+    sig.r.validate_for_verification()?;
+
     // sG = s·G
-    let sg = B8.mul_scalar(&s);
+    let sg = mul_base(&sig.s);
 
     // r + h · x
-    let h = schnorr_hash(&pk, m, &r)?;
-    let pk_h = pk.mul_scalar(&h);
-    let right = r.projective().add(&pk_h.projective());
+    let h = schnorr_hash(pk, m.clone(), &sig.r)?;
+    let pk_h = pk.mul_scalar(&h.to_bigint());
+    let right = sig.r.projective().add(&pk_h.projective());
 
     Ok(sg.equals(right.affine()))
 }
 
 pub fn new_key() -> PrivateKey {
     // https://tools.ietf.org/html/rfc8032#section-5.1.5
+    PrivateKey::generate(&mut rand::thread_rng())
+}
+
+pub fn verify(pk: Point, sig: Signature, msg: BigInt) -> bool {
+    let msg_fr = match msg_to_fr(&msg) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    verify_with(&pk, &sig, |r_b8, a| {
+        let hm_input = vec![r_b8.x, r_b8.y, a.x, a.y, msg_fr];
+        let hm = POSEIDON.hash(hm_input).map_err(Error::HashFailure)?;
+        Ok(Fs::from_fr(&hm))
+    })
+}
+
+pub fn verify_mimc7(pk: Point, sig: Signature, msg: BigInt) -> bool {
+    let msg_fr = match msg_to_fr(&msg) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    verify_with(&pk, &sig, |r_b8, a| {
+        let hm = mimc7::multi_hash(&[r_b8.x, r_b8.y, a.x, a.y, msg_fr], &Fr::zero());
+        Ok(Fs::from_fr(&hm))
+    })
+}
+
+pub fn verify_mimcsponge(pk: Point, sig: Signature, msg: BigInt) -> bool {
+    let msg_fr = match msg_to_fr(&msg) {
+        Ok(m) => m,
+        Err(_) => return false,
+    };
+    verify_with(&pk, &sig, |r_b8, a| {
+        let hm = mimcsponge::multi_hash(&[r_b8.x, r_b8.y, a.x, a.y, msg_fr], &Fr::zero(), 1);
+        Ok(Fs::from_fr(&hm[0]))
+    })
+}
+
+pub fn verify_pedersen(pk: Point, sig: Signature, msg: &[u8]) -> bool {
+    verify_with(&pk, &sig, |r_b8, a| Ok(pedersen_hm(r_b8, a, msg)))
+}
+
+// Checks S·B8 = R8 + 8·hm·A, see PrivateKey::sign_with.
+fn verify_with<F>(pk: &Point, sig: &Signature, hm: F) -> bool
+where
+    F: FnOnce(&Point, &Point) -> Result<Fs, Error>,
+{
+    if pk.validate_for_verification().is_err() || sig.r_b8.validate_for_verification().is_err() {
+        return false;
+    }
+    let hm = match hm(&sig.r_b8, pk) {
+        Result::Err(_) => return false,
+        Result::Ok(hm) => hm,
+    };
+    let l = mul_base(&sig.s);
+    let r = sig.r_b8.projective().add(
+        &pk.mul_scalar(&(8.to_bigint().unwrap() * hm.to_bigint()))
+            .projective(),
+    );
+    l.equals(r.affine())
+}
+
+// hm of EdDSA-Pedersen: the Pedersen hash of R8 || A || msg (points packed), read as a
+// little-endian integer.
+fn pedersen_hm(r_b8: &Point, a: &Point, msg: &[u8]) -> Fs {
+    let hm_input = [&r_b8.compress()[..], &a.compress()[..], msg].concat();
+    let hm = pedersen_hash::hash(&hm_input);
+    Fs::from_bigint(&BigInt::from_bytes_le(Sign::Plus, &hm))
+}
+
+// Messages are field elements: anything outside [0, Q) is rejected rather than reduced.
+fn msg_to_fr(msg: &BigInt) -> Result<Fr, Error> {
+    if msg.sign() == Sign::Minus || msg >= &*Q {
+        return Err(Error::FieldOverflow);
+    }
+    Ok(Fr::from_str(&msg.to_string()).unwrap())
+}
+
+fn msg_le32(msg: &BigInt) -> [u8; 32] {
+    let (_, msg_bytes) = msg.to_bytes_le();
+    let mut msg32: [u8; 32] = [0; 32];
+    msg32[..msg_bytes.len()].copy_from_slice(&msg_bytes[..]);
+    msg32
+}
+
+pub fn verify_fields(pk: Point, sig: Signature, fields: &[Fr]) -> bool {
+    match fields_to_msg(fields) {
+        Ok(m) => verify(
+            pk,
+            sig,
+            BigInt::parse_bytes(to_hex(&m).as_bytes(), 16).unwrap(),
+        ),
+        Err(_) => false,
+    }
+}
+
+pub fn verify_bytes(pk: Point, sig: Signature, msg: &[u8]) -> bool {
+    match bytes_to_msg(msg) {
+        Ok(m) => verify(
+            pk,
+            sig,
+            BigInt::parse_bytes(to_hex(&m).as_bytes(), 16).unwrap(),
+        ),
+        Err(_) => false,
+    }
+}
+
+/// Verifies a batch of EdDSA-Poseidon signatures at once.
+///
+/// Checks a random linear combination of the verification equations,
+/// sum(z_i·s_i)·B8 = sum(z_i·r_b8_i) + sum(8·z_i·hm_i·pk_i), with 128-bit random weights z_i.
+/// Every public key and r_b8 goes through the same subgroup checks as verify, so a batch
+/// accepts exactly the signatures verify accepts.
+///
+/// Returns an error if an entry is malformed (message outside the field, public key or r_b8
+/// not a non-identity point of the prime-order subgroup), and Ok(false) if at least one
+/// signature is invalid; use verify_batch_failures to find which ones.
+pub fn verify_batch(entries: &[(Point, Signature, BigInt)]) -> Result<bool, Error> {
     let mut rng = rand::thread_rng();
-    let sk_raw = rng.gen_biguint(1024).to_bigint().unwrap();
-    let (_, sk_raw_bytes) = sk_raw.to_bytes_be();
-    PrivateKey::import(sk_raw_bytes[..32].to_vec()).unwrap()
+    let mut s_sum = Fs::zero();
+    let mut points: Vec<Point> = Vec::with_capacity(2 * entries.len());
+    let mut scalars: Vec<Fs> = Vec::with_capacity(2 * entries.len());
+    for (pk, sig, msg) in entries {
+        let msg_fr = msg_to_fr(msg)?;
+        pk.validate_for_verification()?;
+        sig.r_b8.validate_for_verification()?;
+        let hm_input = vec![sig.r_b8.x, sig.r_b8.y, pk.x, pk.y, msg_fr];
+        let hm = POSEIDON.hash(hm_input).map_err(Error::HashFailure)?;
+
+        let mut z_bytes: [u8; 16] = [0; 16];
+        rng.fill_bytes(&mut z_bytes);
+        let z = Fs::from_bytes_wide(&z_bytes);
+
+        let mut zs = z;
+        zs.mul_assign(&sig.s);
+        s_sum.add_assign(&zs);
+
+        let mut zh = Fs::from_fr(&hm);
+        zh.mul_assign(&EIGHT);
+        zh.mul_assign(&z);
+
+        points.push(sig.r_b8.clone());
+        scalars.push(z);
+        points.push(pk.clone());
+        scalars.push(zh);
+    }
+    let l = mul_base(&s_sum);
+    let r = multi_scalar_mul(&points, &scalars)?;
+    Ok(l.equals(r))
+}
+
+/// Returns the indices of the entries whose signature does not verify, malformed entries
+/// included. Falls back to verifying one by one only when the batch check fails.
+pub fn verify_batch_failures(entries: &[(Point, Signature, BigInt)]) -> Vec<usize> {
+    if let Ok(true) = verify_batch(entries) {
+        return Vec::new();
+    }
+    entries
+        .iter()
+        .enumerate()
+        .filter(|(_, (pk, sig, msg))| !verify(pk.clone(), sig.clone(), msg.clone()))
+        .map(|(i, _)| i)
+        .collect()
 }
 
-pub fn verify(pk: Point, sig: Signature, msg: BigInt) -> bool {
-    if msg > Q.clone() {
-        return false;
+// (0, -1), the point of order 2, used by the torsion tests.
+#[cfg(test)]
+pub(crate) fn order_two_point() -> Point {
+    let mut minus_one = Fr::one();
+    minus_one.negate();
+    Point {
+        x: Fr::zero(),
+        y: minus_one,
     }
-    let msg_fr: Fr = Fr::from_str(&msg.to_string()).unwrap();
-    let hm_input = vec![sig.r_b8.x, sig.r_b8.y, pk.x, pk.y, msg_fr];
-    let hm = match POSEIDON.hash(hm_input) {
-        Result::Err(_) => return false,
-        Result::Ok(hm) => hm,
-    };
-    let l = B8.mul_scalar(&sig.s);
-    let hm_b = BigInt::parse_bytes(to_hex(&hm).as_bytes(), 16).unwrap();
-    let r = sig
-        .r_b8
-        .projective()
-        .add(&pk.mul_scalar(&(8.to_bigint().unwrap() * hm_b)).projective());
-    l.equals(r.affine())
 }
 
 #[cfg(test)]
@@ -497,6 +1499,45 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extended_add_double() {
+        let p: Point = Point {
+            x: Fr::from_str(
+                "17777552123799933955779906779655732241715742912184938656739573121738514868268",
+            )
+            .unwrap(),
+            y: Fr::from_str(
+                "2626589144620713026669568689430873010625803728049924121243784502389097019475",
+            )
+            .unwrap(),
+        };
+        let q = B8.clone();
+
+        let res_p = p.projective().add(&q.projective()).affine();
+        let res_e = p.extended().add(&q.extended()).affine();
+        assert_eq!(res_p.x, res_e.x);
+        assert_eq!(res_p.y, res_e.y);
+
+        let dbl_p = p.projective().add(&p.projective()).affine();
+        let dbl_e = p.extended().double().affine();
+        assert_eq!(dbl_p.x, dbl_e.x);
+        assert_eq!(dbl_p.y, dbl_e.y);
+
+        // conversions keep the point and T = XY/Z
+        let pe = p.projective().add(&q.projective()).extended();
+        let mut xy = pe.x;
+        xy.mul_assign(&pe.y);
+        let mut tz = pe.t;
+        tz.mul_assign(&pe.z);
+        assert_eq!(xy, tz);
+        assert_eq!(pe.affine().x, res_p.x);
+        assert_eq!(pe.projective().affine().y, res_p.y);
+
+        let id = PointExtended::identity().add(&p.extended()).affine();
+        assert_eq!(id.x, p.x);
+        assert_eq!(id.y, p.y);
+    }
+
     #[test]
     fn test_mul_scalar() {
         let p: Point = Point {
@@ -550,6 +1591,202 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_point_validation() {
+        assert!(B8.is_on_curve());
+        assert!(B8.is_in_subgroup());
+        assert!(Point::new(B8.x, B8.y).is_ok());
+
+        let mut y = B8.y;
+        y.add_assign(&Fr::one());
+        assert_eq!(Point::new(B8.x, y).unwrap_err(), Error::NotOnCurve);
+
+        let t = order_two_point();
+        assert!(t.is_on_curve());
+        assert!(!t.is_in_subgroup());
+        assert_eq!(Point::new(t.x, t.y).unwrap_err(), Error::NotInSubgroup);
+    }
+
+    #[test]
+    fn test_verify_rejects_torsion_and_identity() {
+        let sk = new_key();
+        let pk = sk.public();
+        let msg = 5.to_bigint().unwrap();
+        let sig = sk.sign(msg.clone()).unwrap();
+        assert!(verify(pk.clone(), sig.clone(), msg.clone()));
+
+        // pk + (0, -1) passes the verification equation since 8·hm kills the torsion part
+        let t = order_two_point();
+        let pk_t = pk.projective().add(&t.projective()).affine();
+        assert!(!verify(pk_t.clone(), sig.clone(), msg.clone()));
+
+        let identity = Point {
+            x: Fr::zero(),
+            y: Fr::one(),
+        };
+        assert!(!verify(identity.clone(), sig, msg.clone()));
+
+        let sig = sk.sign_schnorr(msg.clone()).unwrap();
+        assert_eq!(
+            PublicKey::from_point(pk_t).unwrap_err(),
+            Error::NotInSubgroup
+        );
+        assert_eq!(
+            PublicKey::from_point(identity.clone()).unwrap_err(),
+            Error::IdentityPoint
+        );
+        let sig = SchnorrSignature {
+            r: identity,
+            s: sig.s,
+        };
+        assert_eq!(
+            verify_schnorr(&sk.public_key(), &msg, &sig).unwrap_err(),
+            Error::IdentityPoint
+        );
+    }
+
+    #[test]
+    fn test_mul_base() {
+        let mut rng = rand::thread_rng();
+        let mut l_minus_one = Fs::zero();
+        l_minus_one.sub_assign(&Fs::one());
+        let scalars = vec![
+            Fs::zero(),
+            Fs::one(),
+            l_minus_one,
+            Fs::random(&mut rng),
+            Fs::random(&mut rng),
+        ];
+        for s in scalars {
+            let expected = B8.mul_scalar(&s.to_bigint());
+            let res = mul_base(&s);
+            assert_eq!(res.x, expected.x);
+            assert_eq!(res.y, expected.y);
+        }
+    }
+
+    #[test]
+    fn test_generate_key() {
+        use rand::{rngs::StdRng, SeedableRng};
+
+        let sk0 = PrivateKey::generate(&mut StdRng::seed_from_u64(42));
+        let sk1 = PrivateKey::generate(&mut StdRng::seed_from_u64(42));
+        assert_eq!(sk0.key, sk1.key);
+        assert_eq!(sk0.public(), sk1.public());
+
+        let sk2 = PrivateKey::generate(&mut StdRng::seed_from_u64(43));
+        assert_ne!(sk0.key, sk2.key);
+
+        // the byte-level derivation matches prv2bigint
+        for sk in [sk0, sk2, new_key()] {
+            assert_eq!(*sk.scalar(), Fs::from_bigint(&sk.scalar_key()));
+        }
+    }
+
+    #[test]
+    fn test_private_key_bytes_hex() {
+        let sk = new_key();
+        let b = sk.to_bytes().unwrap();
+        assert_eq!(PrivateKey::from_bytes(&b).public(), sk.public());
+        assert_eq!(
+            PrivateKey::import(b.to_vec()).unwrap().to_bytes().unwrap(),
+            b
+        );
+
+        let h = sk.to_hex().unwrap();
+        assert_eq!(h.len(), 64);
+        assert_eq!(PrivateKey::from_hex(&h).unwrap().public(), sk.public());
+        assert_eq!(
+            PrivateKey::from_hex(&format!("0x{}", h))
+                .unwrap()
+                .to_bytes()
+                .unwrap(),
+            b
+        );
+
+        assert_eq!(
+            PrivateKey::from_hex(&h[..62]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 32,
+                actual: 31
+            }
+        );
+        assert_eq!(
+            PrivateKey::from_hex(&"zz".repeat(32)).unwrap_err(),
+            Error::InvalidHex
+        );
+        assert_eq!(
+            PrivateKey::import(vec![1; 31]).unwrap_err().to_string(),
+            "invalid length: expected 32, got 31"
+        );
+    }
+
+    #[test]
+    fn test_private_key_from_scalar() {
+        let sk = new_key();
+        let sk_s = PrivateKey::from_scalar(&Fs::from_bigint(&sk.scalar_key())).unwrap();
+        assert!(sk_s.is_scalar());
+        assert!(!sk.is_scalar());
+        assert_eq!(sk_s.public(), sk.public());
+
+        let msg = 5.to_bigint().unwrap();
+        let sig = sk_s.sign(msg.clone()).unwrap();
+        assert!(verify(sk.public(), sig, msg.clone()));
+        let sig = sk_s.sign_schnorr(msg.clone()).unwrap();
+        assert!(verify_schnorr(&sk.public_key(), &msg, &sig).unwrap());
+
+        // the scalar must not come back as a seed through the byte and hex API
+        assert_eq!(sk_s.to_bytes().unwrap_err(), Error::ScalarKey);
+        assert_eq!(sk_s.to_hex().unwrap_err(), Error::ScalarKey);
+        assert_eq!(
+            PrivateKey::from_scalar(&Fs::zero()).unwrap_err(),
+            Error::IdentityPoint
+        );
+    }
+
+    #[test]
+    fn test_public_key() {
+        let sk = PrivateKey::import(
+            hex::decode("0001020304050607080900010203040506070809000102030405060708090001")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = sk.public_key();
+        assert_eq!(pk.point(), &sk.public());
+
+        let pk2 = PublicKey::decompress(&pk.compress()).unwrap();
+        assert_eq!(pk2, pk);
+        assert_eq!(PublicKey::from_hex(&pk.to_hex()).unwrap(), pk);
+        assert_eq!(pk.to_string(), pk.to_hex());
+
+        // a point outside the subgroup is not a key
+        let t = order_two_point();
+        let pk_t = pk.point().add(&t);
+        assert_eq!(
+            PublicKey::decompress(&pk_t.compress()).unwrap_err(),
+            Error::NotInSubgroup
+        );
+
+        let h = pk.key_hash().unwrap();
+        assert_eq!(
+            h.to_string(),
+            "Fr(0x11e4f0cc5af0337d70fe7a9452065ceda2841d4545ffcc2b1bbefe09a1f878f0)"
+        );
+        assert_eq!(h, POSEIDON.hash(vec![pk.point().x, pk.point().y]).unwrap());
+        assert_eq!(
+            pk.address().unwrap().to_vec(),
+            hex::decode("52065ceda2841d4545ffcc2b1bbefe09a1f878f0").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_private_key_debug_redacted() {
+        let sk = PrivateKey::import(vec![0xab; 32]).unwrap();
+        let s = format!("{:?}", sk);
+        assert_eq!(s, "PrivateKey(<redacted>)");
+        assert!(!s.contains("ab"));
+    }
+
     #[test]
     fn test_new_key_sign_verify_0() {
         let sk = new_key();
@@ -570,6 +1807,169 @@ mod tests {
         assert_eq!(v, true);
     }
 
+    #[test]
+    fn test_point_group_ops() {
+        let mut rng = rand::thread_rng();
+        let a = Fs::random(&mut rng);
+        let b = Fs::random(&mut rng);
+        let p = mul_base(&a);
+        let q = mul_base(&b);
+        let id = Point::identity();
+
+        assert!(id.is_identity());
+        assert!(!p.is_identity());
+        assert_eq!(&p + &id, p);
+        assert!((&p - &p).is_identity());
+        assert!((&p + &(-&p)).is_identity());
+        assert_eq!(p.double(), &p + &p);
+        assert_eq!(-(-p.clone()), p);
+        assert_eq!(p.clone() + q.clone(), q.clone() + p.clone());
+        assert_eq!(p.clone() - q.clone() + q.clone(), p);
+
+        let mut ab = a;
+        ab.add_assign(&b);
+        assert_eq!(&p + &q, mul_base(&ab));
+        assert_eq!(B8.clone() * a, p);
+        assert_eq!(&*B8 * &ab, &p + &q);
+        assert!(p.equals(p.clone()));
+    }
+
+    #[test]
+    fn test_point_eq_hash() {
+        use std::collections::HashSet;
+
+        let p = B8.projective();
+        // same point with Z = 2
+        let mut two = Fr::one();
+        two.double();
+        let mut p2 = p;
+        p2.x.mul_assign(&two);
+        p2.y.mul_assign(&two);
+        p2.z.mul_assign(&two);
+        assert_eq!(p, p2);
+        assert_ne!(p, identity_projective());
+        assert_eq!(p.extended(), p2.extended());
+        assert_ne!(p.extended(), PointExtended::identity());
+
+        let mut set = HashSet::new();
+        set.insert(p);
+        assert!(set.contains(&p2));
+
+        let mut set = HashSet::new();
+        set.insert(B8.clone());
+        set.insert(p2.affine());
+        set.insert(B8.double());
+        assert_eq!(set.len(), 2);
+    }
+
+    #[test]
+    fn test_mul_scalar_ct() {
+        let mut rng = rand::thread_rng();
+        let mut l_minus_one = Fs::zero();
+        l_minus_one.sub_assign(&Fs::one());
+        let p = mul_base(&Fs::random(&mut rng));
+        let scalars = vec![Fs::zero(), Fs::one(), l_minus_one, Fs::random(&mut rng)];
+        for s in scalars {
+            let expected = p.mul_scalar(&s.to_bigint());
+            let res = p.mul_scalar_ct(&s);
+            assert_eq!(res.x, expected.x);
+            assert_eq!(res.y, expected.y);
+
+            let expected = mul_base(&s);
+            let res = mul_base_ct(&s);
+            assert_eq!(res.x, expected.x);
+            assert_eq!(res.y, expected.y);
+        }
+    }
+
+    #[test]
+    fn test_conditional_select() {
+        let a = B8.extended();
+        let b = PointExtended::identity();
+        let r = PointExtended::conditional_select(&a, &b, Choice::from(0));
+        assert_eq!(r.affine().x, B8.x);
+        let r = PointExtended::conditional_select(&a, &b, Choice::from(1));
+        assert!(r.affine().x.is_zero());
+
+        let a = B8.projective();
+        let b = identity_projective();
+        let r = PointProjective::conditional_select(&a, &b, Choice::from(0));
+        assert_eq!(r.affine().y, B8.y);
+        let r = PointProjective::conditional_select(&a, &b, Choice::from(1));
+        assert_eq!(r.affine().y, Fr::one());
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let mut entries = Vec::new();
+        for i in 0..6 {
+            let sk = new_key();
+            let msg = (1000 + i).to_bigint().unwrap();
+            let sig = sk.sign(msg.clone()).unwrap();
+            entries.push((sk.public(), sig, msg));
+        }
+        assert_eq!(verify_batch(&[]), Ok(true));
+        assert_eq!(verify_batch(&entries), Ok(true));
+        assert!(verify_batch_failures(&entries).is_empty());
+
+        entries[2].2 = 5.to_bigint().unwrap();
+        entries[4].1.s.add_assign(&Fs::one());
+        assert_eq!(verify_batch(&entries), Ok(false));
+        assert_eq!(verify_batch_failures(&entries), vec![2, 4]);
+
+        // the signer picks R' = r·B8 + T with T of order 2 and computes s over hm(R')
+        let sk = new_key();
+        let pk = sk.public();
+        let msg = 7.to_bigint().unwrap();
+        let r = Fs::random(&mut rand::thread_rng());
+        let r_b8 = mul_base(&r).add(&order_two_point());
+        let hm = POSEIDON
+            .hash(vec![r_b8.x, r_b8.y, pk.x, pk.y, msg_to_fr(&msg).unwrap()])
+            .unwrap();
+        let mut s = *sk.scalar();
+        s.mul_assign(&EIGHT);
+        s.mul_assign(&Fs::from_fr(&hm));
+        s.add_assign(&r);
+        let sig = Signature { r_b8, s };
+        assert!(!verify(pk.clone(), sig.clone(), msg.clone()));
+        assert_eq!(
+            verify_batch(&[(pk.clone(), sig.clone(), msg.clone())]),
+            Err(Error::NotInSubgroup)
+        );
+        entries[0] = (pk, sig, msg);
+        assert_eq!(verify_batch(&entries), Err(Error::NotInSubgroup));
+        assert_eq!(verify_batch_failures(&entries), vec![0, 2, 4]);
+
+        entries[1].1.r_b8.x.add_assign(&Fr::one());
+        assert_eq!(verify_batch(&entries), Err(Error::NotInSubgroup));
+        assert_eq!(verify_batch_failures(&entries), vec![0, 1, 2, 4]);
+
+        entries[3].2 = -(3.to_bigint().unwrap());
+        assert_eq!(verify_batch(&entries), Err(Error::NotInSubgroup));
+        assert_eq!(verify_batch(&entries[3..4]), Err(Error::FieldOverflow));
+        entries[3].2 = Q.clone();
+        assert_eq!(verify_batch(&entries[3..4]), Err(Error::FieldOverflow));
+        assert_eq!(verify_batch_failures(&entries), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_message_outside_field() {
+        let sk = new_key();
+        let minus_one = -(1.to_bigint().unwrap());
+        assert_eq!(
+            sk.sign(minus_one.clone()).unwrap_err(),
+            Error::FieldOverflow
+        );
+        assert_eq!(sk.sign(Q.clone()).unwrap_err(), Error::FieldOverflow);
+        assert!(sk.sign(&*Q - 1).is_ok());
+
+        let sig = sk.sign(0.to_bigint().unwrap()).unwrap();
+        assert!(verify(sk.public(), sig.clone(), 0.to_bigint().unwrap()));
+        // Q would reduce to 0
+        assert!(!verify(sk.public(), sig.clone(), Q.clone()));
+        assert!(!verify(sk.public(), sig, minus_one));
+    }
+
     #[test]
     fn test_point_compress_decompress() {
         let p: Point = Point {
@@ -674,14 +2074,166 @@ mod tests {
     }
 
     #[test]
-    fn test_schnorr_signature() {
+    fn test_signature_decompress_non_canonical_s() {
+        let sk = new_key();
+        let sig = sk.sign(5.to_bigint().unwrap()).unwrap();
+        let mut compressed_sig = sig.compress();
+        let (_, l) = SUBORDER.to_bytes_le();
+        compressed_sig[32..].copy_from_slice(&l);
+        assert_eq!(
+            decompress_signature(&compressed_sig).unwrap_err(),
+            Error::FieldOverflow
+        );
+    }
+
+    #[test]
+    fn test_sign_verify_bytes() {
+        let sk = new_key();
+        let pk = sk.public();
+        let msg = b"transfer 100 tokens to account 42";
+        let sig = sk.sign_bytes(msg).unwrap();
+        assert!(verify_bytes(pk.clone(), sig.clone(), msg));
+        assert!(!verify_bytes(
+            pk.clone(),
+            sig.clone(),
+            b"transfer 900 tokens to account 42"
+        ));
+
+        let sig = sk.sign_bytes(&[]).unwrap();
+        assert!(verify_bytes(pk.clone(), sig.clone(), &[]));
+        assert!(!verify_bytes(pk, sig, &[0]));
+    }
+
+    #[test]
+    fn test_sign_verify_fields() {
+        let fields: Vec<Fr> = (1..=20)
+            .map(|i| Fr::from_str(&i.to_string()).unwrap())
+            .collect();
+        let long = &fields[..17];
+        let short = [POSEIDON.hash(long[..16].to_vec()).unwrap(), long[16]];
+
         let sk = new_key();
         let pk = sk.public();
+        let sig = sk.sign_fields(&fields[..4]).unwrap();
+        assert!(verify_fields(pk.clone(), sig.clone(), &fields[..4]));
+        assert!(!verify_fields(pk.clone(), sig.clone(), &fields[1..5]));
+        assert!(!verify_fields(pk.clone(), sig, &[]));
+
+        let sig = sk.sign_fields(&fields).unwrap();
+        assert!(verify_fields(pk.clone(), sig, &fields));
+        let sig = sk.sign_fields(&short).unwrap();
+        assert!(!verify_fields(pk, sig, long));
+    }
+
+    #[test]
+    fn test_schnorr_signature() {
+        let sk = new_key();
+        let pk = sk.public_key();
 
         let msg = BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap();
-        let (s, e) = sk.sign_schnorr(msg.clone()).unwrap();
-        let verification = verify_schnorr(pk, msg, s, e).unwrap();
+        let sig = sk.sign_schnorr(msg.clone()).unwrap();
+        let verification = verify_schnorr(&pk, &msg, &sig).unwrap();
         assert_eq!(true, verification);
+
+        let minus_one = -(1.to_bigint().unwrap());
+        assert_eq!(
+            sk.sign_schnorr(minus_one.clone()).unwrap_err(),
+            Error::FieldOverflow
+        );
+        assert_eq!(
+            sk.sign_schnorr(Q.clone()).unwrap_err(),
+            Error::FieldOverflow
+        );
+        assert_eq!(
+            verify_schnorr(&pk, &minus_one, &sig).unwrap_err(),
+            Error::FieldOverflow
+        );
+    }
+
+    #[test]
+    fn test_schnorr_signature_compress_decompress() {
+        let sk = new_key();
+        let pk = sk.public_key();
+        let msg = BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap();
+        let sig = sk.sign_schnorr(msg.clone()).unwrap();
+
+        let compressed_sig = sig.compress();
+        let decompressed_sig = decompress_schnorr_signature(&compressed_sig).unwrap();
+        assert_eq!(decompressed_sig.r, sig.r);
+        assert_eq!(decompressed_sig.s, sig.s);
+        assert!(verify_schnorr(&pk, &msg, &decompressed_sig).unwrap());
+
+        // s + SUBORDER is rejected
+        let mut malleated = compressed_sig;
+        let s_plus_l = sig.s.to_bigint() + &*SUBORDER;
+        let (_, s_bytes) = s_plus_l.to_bytes_le();
+        malleated[32..].copy_from_slice(&[0; 32]);
+        malleated[32..32 + s_bytes.len()].copy_from_slice(&s_bytes);
+        assert_eq!(
+            decompress_schnorr_signature(&malleated).unwrap_err(),
+            Error::FieldOverflow
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            PrivateKey::import(vec![0; 31]).err(),
+            Some(Error::InvalidLength {
+                expected: 32,
+                actual: 31
+            })
+        );
+        assert_eq!(
+            recover_point(Q.clone(), false).err(),
+            Some(Error::FieldOverflow)
+        );
+        let sk = new_key();
+        assert_eq!(sk.sign(&Q.clone() + 1).err(), Some(Error::FieldOverflow));
+    }
+
+    #[test]
+    fn test_schnorr_deterministic_nonce() {
+        let sk = PrivateKey::import(
+            hex::decode("0001020304050607080900010203040506070809000102030405060708090001")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = sk.public_key();
+        let msg = BigInt::parse_bytes(b"123456789012345678901234567890", 10).unwrap();
+
+        let sig = sk.sign_schnorr(msg.clone()).unwrap();
+        assert_eq!(
+            sig.r.x.to_string(),
+            "Fr(0x01f3a5b68628bb33ff8e1f3ef1e15a7ade0a67b88afbdd74c1870948a41102b8)"
+        );
+        assert_eq!(
+            sig.r.y.to_string(),
+            "Fr(0x21e93bbde8ab67ce9c47d851b2e748c65325abf88cbc8d4e7231c84bcce36c72)"
+        );
+        assert_eq!(
+            sig.s,
+            Fs::from_str(
+                "2508586918073840253556670912557269550212428715603091951894392267274645632898"
+            )
+            .unwrap()
+        );
+        assert!(verify_schnorr(&pk, &msg, &sig).unwrap());
+
+        // same key and message, same signature
+        let sig2 = sk.sign_schnorr(msg.clone()).unwrap();
+        assert_eq!(sig.r, sig2.r);
+        assert_eq!(sig.s, sig2.s);
+
+        // the EdDSA nonce for the same message is a different one
+        let eddsa_sig = sk.sign(msg.clone()).unwrap();
+        assert_ne!(eddsa_sig.r_b8, sig.r);
+
+        let sig3 = sk
+            .sign_schnorr_with_rng(msg.clone(), &mut rand::thread_rng())
+            .unwrap();
+        assert_ne!(sig3.r, sig.r);
+        assert!(verify_schnorr(&pk, &msg, &sig3).unwrap());
     }
 
     #[test]
@@ -729,10 +2281,108 @@ mod tests {
             "Fr(0x2202bebcf57b820863e0acc88970b6ca7d987a0d513c2ddeb42e3f5d31b4eddf)"
         );
         assert_eq!(
-            sig.s.to_string(),
-            "1672775540645840396591609181675628451599263765380031905495115170613215233181"
+            sig.s,
+            Fs::from_str(
+                "1672775540645840396591609181675628451599263765380031905495115170613215233181"
+            )
+            .unwrap()
         );
         let v = verify(pk, sig, msg);
         assert_eq!(v, true);
     }
+
+    #[test]
+    fn test_circomlib_testvector_mimc() {
+        let sk = PrivateKey::import(
+            hex::decode("0001020304050607080900010203040506070809000102030405060708090001")
+                .unwrap(),
+        )
+        .unwrap();
+        let pk = sk.public();
+        let msg = BigInt::from_bytes_le(Sign::Plus, &hex::decode("00010203040506070809").unwrap());
+
+        // same nonce as signPoseidon, only S changes
+        let sig = sk.sign_mimc7(msg.clone()).unwrap();
+        assert_eq!(
+            sig.r_b8.x.to_string(),
+            "Fr(0x192b4e51adf302c8139d356d0e08e2404b5ace440ef41fc78f5c4f2428df0765)"
+        );
+        assert_eq!(
+            sig.r_b8.y.to_string(),
+            "Fr(0x2202bebcf57b820863e0acc88970b6ca7d987a0d513c2ddeb42e3f5d31b4eddf)"
+        );
+        assert_eq!(
+            sig.s,
+            Fs::from_str(
+                "2523202440825208709475937830811065542425109372212752003460238913256192595070"
+            )
+            .unwrap()
+        );
+        assert!(verify_mimc7(pk.clone(), sig.clone(), msg.clone()));
+        assert!(!verify(pk.clone(), sig, msg.clone()));
+
+        let sig = sk.sign_mimcsponge(msg.clone()).unwrap();
+        assert_eq!(
+            sig.s,
+            Fs::from_str(
+                "1868336918738674306327358602987493427631678603535639134028485964115448322340"
+            )
+            .unwrap()
+        );
+        assert!(verify_mimcsponge(pk.clone(), sig.clone(), msg.clone()));
+        assert!(!verify_mimc7(pk.clone(), sig.clone(), msg));
+
+        let minus_one = -(1.to_bigint().unwrap());
+        assert_eq!(
+            sk.sign_mimc7(minus_one.clone()).unwrap_err(),
+            Error::FieldOverflow
+        );
+        assert_eq!(
+            sk.sign_mimcsponge(Q.clone()).unwrap_err(),
+            Error::FieldOverflow
+        );
+        assert!(!verify_mimc7(pk.clone(), sig.clone(), minus_one.clone()));
+        assert!(!verify_mimcsponge(pk, sig, minus_one));
+    }
+
+    #[test]
+    fn test_sign_verify_pedersen() {
+        // circomlib's "Sign (using Pedersen) a single 10 bytes from 0 to 9"
+        let sk = PrivateKey::import(
+            hex::decode("0001020304050607080900010203040506070809000102030405060708090001")
+                .unwrap(),
+        )
+        .unwrap();
+        let msg = hex::decode("00010203040506070809").unwrap();
+        let sig = sk.sign_pedersen(&msg).unwrap();
+        assert_eq!(
+            sig.r_b8.x,
+            Fr::from_str(
+                "21253904451576600568378459528205653033385900307028841334532552830614710476912"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            sig.r_b8.y,
+            Fr::from_str(
+                "20125634407542493427571099944365246191501563803226486072348038614369379124499"
+            )
+            .unwrap()
+        );
+        assert_eq!(
+            sig.s,
+            Fs::from_str(
+                "2129243915978267980511515511350111723623685317644064470882297086073041379651"
+            )
+            .unwrap()
+        );
+        assert!(verify_pedersen(sk.public(), sig, &msg));
+
+        let sk = new_key();
+        let pk = sk.public();
+        let msg = hex::decode("00010203040506070809").unwrap();
+        let sig = sk.sign_pedersen(&msg).unwrap();
+        assert!(verify_pedersen(pk.clone(), sig.clone(), &msg));
+        assert!(!verify_pedersen(pk, sig, &msg[1..]));
+    }
 }
@@ -0,0 +1,178 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Mapping of messages that are not a single field element to the field element that gets
+// signed, so that circuits can recompute it with Poseidon.
+
+use std::cmp::min;
+
+use ff::*;
+
+use crate::{Error, Fr, POSEIDON};
+
+const HASH_BYTES_CHUNK: usize = 31;
+pub(crate) const POSEIDON_FRAME: usize = 16;
+
+/// Poseidon hash of a byte string, with the layout of go-iden3-crypto's poseidon.HashBytes:
+/// the bytes are cut into 31-byte chunks read as big-endian integers (the last one zero-padded
+/// on the right), which fill frames of 16 inputs. A full frame is hashed and its hash becomes
+/// the first input of the next frame. An empty input hashes a frame of zeros.
+///
+/// Trailing zero bytes are absorbed by the padding, so b and b || 0x00 hash the same; use
+/// bytes_to_msg when that matters.
+pub fn poseidon_hash_bytes(b: &[u8]) -> Result<Fr, Error> {
+    let mut inputs: Vec<Fr> = vec![Fr::zero(); POSEIDON_FRAME];
+    let mut hash: Option<Fr> = None;
+    let mut dirty = false;
+    let mut k = 0;
+    for chunk in b.chunks(HASH_BYTES_CHUNK) {
+        let mut buf: [u8; 32] = [0; 32];
+        buf[1..1 + chunk.len()].copy_from_slice(chunk);
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        repr.read_be(&buf[..]).unwrap();
+        // 248 bits, always below Q
+        inputs[k] = Fr::from_repr(repr).unwrap();
+        dirty = true;
+        if k == POSEIDON_FRAME - 1 {
+            let h = POSEIDON.hash(inputs).map_err(Error::HashFailure)?;
+            inputs = vec![Fr::zero(); POSEIDON_FRAME];
+            inputs[0] = h;
+            hash = Some(h);
+            dirty = false;
+            k = 1;
+        } else {
+            k += 1;
+        }
+    }
+    match hash {
+        Some(h) if !dirty => Ok(h),
+        _ => POSEIDON.hash(inputs).map_err(Error::HashFailure),
+    }
+}
+
+/// Maps a byte message to the field element signed by sign_bytes:
+/// Poseidon(poseidon_hash_bytes(b), len(b)). Binding the length keeps messages that differ
+/// only in trailing zero bytes apart.
+pub fn bytes_to_msg(b: &[u8]) -> Result<Fr, Error> {
+    let len = Fr::from_repr(<Fr as PrimeField>::Repr::from(b.len() as u64)).unwrap();
+    POSEIDON
+        .hash(vec![poseidon_hash_bytes(b)?, len])
+        .map_err(Error::HashFailure)
+}
+
+/// Maps a vector of field elements to the field element signed by sign_fields.
+///
+/// The length is hashed together with the first 15 elements, h = Poseidon(n, f_1, ..., f_k),
+/// so up to 15 elements take a single hash in a circuit. Longer vectors are chained: each
+/// following group of up to 15 elements is hashed together with the previous hash,
+/// h = Poseidon(h, f_i, ..., f_j). The length input keeps vectors of different lengths apart:
+/// a chained step starts with a hash where a short vector starts with its (small) length, and
+/// first steps of long vectors differ in n.
+pub fn fields_to_msg(fields: &[Fr]) -> Result<Fr, Error> {
+    if fields.is_empty() {
+        return Err(Error::InvalidLength {
+            expected: 1,
+            actual: 0,
+        });
+    }
+    let first = min(fields.len(), POSEIDON_FRAME - 1);
+    let mut inputs =
+        vec![Fr::from_repr(<Fr as PrimeField>::Repr::from(fields.len() as u64)).unwrap()];
+    inputs.extend_from_slice(&fields[..first]);
+    let mut h = POSEIDON.hash(inputs).map_err(Error::HashFailure)?;
+    for group in fields[first..].chunks(POSEIDON_FRAME - 1) {
+        let mut inputs = vec![h];
+        inputs.extend_from_slice(group);
+        h = POSEIDON.hash(inputs).map_err(Error::HashFailure)?;
+    }
+    Ok(h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_poseidon_hash_bytes() {
+        let b: Vec<u8> = (0..512).map(|i| i as u8).collect();
+        assert_eq!(
+            poseidon_hash_bytes(&[]).unwrap().to_string(),
+            "Fr(0x0f63cd0c9fbe679a562469831d8e810c9d33cc2409695b8e6a893e627ea952d1)"
+        );
+        assert_eq!(
+            poseidon_hash_bytes(b"hello babyjubjub")
+                .unwrap()
+                .to_string(),
+            "Fr(0x26c91398f8d4e40849a9a31028f62d6e0b9bbdec314f46f3fcc24595c2dbf1ca)"
+        );
+        // exactly one full frame of 16 chunks
+        assert_eq!(
+            poseidon_hash_bytes(&b[..496]).unwrap().to_string(),
+            "Fr(0x148862592cbaba127837c4dfa888b986868328df41671eb20d2761e6ddbcbe58)"
+        );
+        // one more chunk, chained into a second frame
+        assert_eq!(
+            poseidon_hash_bytes(&b).unwrap().to_string(),
+            "Fr(0x043e1549405c9f6616efb5716ed4581c410d5dda46e4ac20e7ccdc539bffdf62)"
+        );
+
+        // trailing zeros only differ once the length is bound in
+        let mut b0 = b.clone();
+        b0.push(0);
+        assert_eq!(poseidon_hash_bytes(&b0), poseidon_hash_bytes(&b));
+        assert_ne!(bytes_to_msg(&b0), bytes_to_msg(&b));
+        assert_eq!(
+            bytes_to_msg(b"hello babyjubjub").unwrap(),
+            Fr::from_str(
+                "1560991658557447438248108605032974473424442154216972496314607510327390705860"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_fields_to_msg() {
+        let fields: Vec<Fr> = (1..=20)
+            .map(|i| Fr::from_str(&i.to_string()).unwrap())
+            .collect();
+
+        let fr = |n: usize| Fr::from_str(&n.to_string()).unwrap();
+        let with_len = |f: &[Fr]| [&[fr(f.len())], f].concat();
+
+        // nonce, amount, token id, recipient: a single Poseidon hash
+        assert_eq!(
+            fields_to_msg(&fields[..4]).unwrap(),
+            POSEIDON.hash(with_len(&fields[..4])).unwrap()
+        );
+        assert_eq!(
+            fields_to_msg(&fields[..15]).unwrap(),
+            POSEIDON.hash(with_len(&fields[..15])).unwrap()
+        );
+        // 20 elements: the length and 15, then the hash and the last 5
+        let mut inputs = vec![fr(20)];
+        inputs.extend_from_slice(&fields[..15]);
+        let h = POSEIDON.hash(inputs).unwrap();
+        let mut inputs = vec![h];
+        inputs.extend_from_slice(&fields[15..]);
+        assert_eq!(
+            fields_to_msg(&fields).unwrap(),
+            POSEIDON.hash(inputs).unwrap()
+        );
+
+        // a long vector does not collide with the short vector of its chaining inputs
+        let long = &fields[..17];
+        let mut inputs = vec![fr(17)];
+        inputs.extend_from_slice(&long[..15]);
+        let short = [POSEIDON.hash(inputs).unwrap(), long[15], long[16]];
+        assert_ne!(fields_to_msg(long).unwrap(), fields_to_msg(&short).unwrap());
+        let short = [POSEIDON.hash(long[..16].to_vec()).unwrap(), long[16]];
+        assert_ne!(fields_to_msg(long).unwrap(), fields_to_msg(&short).unwrap());
+        assert_eq!(
+            fields_to_msg(&[]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 1,
+                actual: 0
+            }
+        );
+    }
+}
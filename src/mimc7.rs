@@ -0,0 +1,91 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// MiMC7 hash, compatible with circomlib's mimc7.js: 91 rounds of (x + k + c_i)^7 with round
+// constants derived from keccak256("mimc").
+
+use ff::*;
+use num_bigint::{BigInt, Sign};
+
+use crate::{Fr, Q};
+
+const SEED: &str = "mimc";
+const N_ROUNDS: usize = 91;
+
+lazy_static! {
+    static ref CONSTANTS: Vec<Fr> = constants(SEED, N_ROUNDS);
+}
+
+// c_0 = 0 and c_i = keccak256^(i+1)(seed) mod q, hashing the full 32 bytes at each step as
+// circomlib does.
+pub(crate) fn constants(seed: &str, n_rounds: usize) -> Vec<Fr> {
+    let mut cts: Vec<Fr> = vec![Fr::zero(); n_rounds];
+    let mut c = tiny_keccak::keccak256(seed.as_bytes());
+    for ct in cts.iter_mut().skip(1) {
+        c = tiny_keccak::keccak256(&c);
+        let n = BigInt::from_bytes_be(Sign::Plus, &c) % &*Q;
+        *ct = Fr::from_str(&n.to_string()).unwrap();
+    }
+    cts
+}
+
+pub fn hash(x_in: &Fr, k: &Fr) -> Fr {
+    let mut r = Fr::zero();
+    for (i, c) in CONSTANTS.iter().enumerate() {
+        let mut t = if i == 0 { *x_in } else { r };
+        t.add_assign(k);
+        t.add_assign(c);
+        // t^7
+        let mut t2 = t;
+        t2.square();
+        r = t2;
+        r.square();
+        r.mul_assign(&t2);
+        r.mul_assign(&t);
+    }
+    r.add_assign(k);
+    r
+}
+
+/// circomlib's mimc7.multiHash: r_0 = key, r_i = r_{i-1} + x_i + hash(x_i, r_{i-1}).
+pub fn multi_hash(arr: &[Fr], key: &Fr) -> Fr {
+    let mut r = *key;
+    for x in arr {
+        let h = hash(x, &r);
+        r.add_assign(x);
+        r.add_assign(&h);
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mimc7() {
+        let fr = |s: &str| Fr::from_str(s).unwrap();
+        assert_eq!(
+            hash(&fr("1"), &fr("2")),
+            fr("10594780656576967754230020536574539122676596303354946869887184401991294982664")
+        );
+
+        // circomlib / iden3js test vectors
+        assert_eq!(
+            multi_hash(&[fr("12")], &Fr::zero()).to_string(),
+            "Fr(0x237c92644dbddb86d8a259e0e923aaab65a93f1ec5758b8799988894ac0958fd)"
+        );
+        assert_eq!(
+            multi_hash(&[fr("78"), fr("41")], &Fr::zero()).to_string(),
+            "Fr(0x067f3202335ea256ae6e6aadcd2d5f7f4b06a00b2d1e0de903980d5ab552dc70)"
+        );
+        assert_eq!(
+            multi_hash(&[fr("12"), fr("45")], &Fr::zero()).to_string(),
+            "Fr(0x15ff7fe9793346a17c3150804bcb36d161c8662b110c50f55ccb7113948d8879)"
+        );
+        assert_eq!(
+            multi_hash(&[fr("12"), fr("45"), fr("78"), fr("41")], &Fr::zero()).to_string(),
+            "Fr(0x284bc1f34f335933a23a433b6ff3ee179d682cd5e5e2fcdd2d964afa85104beb)"
+        );
+    }
+}
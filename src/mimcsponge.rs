@@ -0,0 +1,86 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// MiMC sponge, compatible with circomlib's mimcsponge.js: a 220-round Feistel network with
+// x^5 and round constants derived from keccak256("mimcsponge").
+
+use ff::*;
+
+use crate::{mimc7, Fr};
+
+const SEED: &str = "mimcsponge";
+const N_ROUNDS: usize = 220;
+
+lazy_static! {
+    static ref CONSTANTS: Vec<Fr> = {
+        let mut cts = mimc7::constants(SEED, N_ROUNDS);
+        cts[N_ROUNDS - 1] = Fr::zero();
+        cts
+    };
+}
+
+/// The MiMC Feistel permutation keyed with k, returns (xL, xR).
+pub fn hash(xl_in: &Fr, xr_in: &Fr, k: &Fr) -> (Fr, Fr) {
+    let mut xl = *xl_in;
+    let mut xr = *xr_in;
+    for (i, c) in CONSTANTS.iter().enumerate() {
+        let mut t = xl;
+        t.add_assign(k);
+        t.add_assign(c);
+        // t^5
+        let mut t5 = t;
+        t5.square();
+        t5.square();
+        t5.mul_assign(&t);
+        if i < N_ROUNDS - 1 {
+            let mut xl_new = xr;
+            xl_new.add_assign(&t5);
+            xr = xl;
+            xl = xl_new;
+        } else {
+            xr.add_assign(&t5);
+        }
+    }
+    (xl, xr)
+}
+
+/// circomlib's mimcsponge.multiHash: absorbs arr one element at a time into the left half
+/// and squeezes num_outputs elements.
+pub fn multi_hash(arr: &[Fr], key: &Fr, num_outputs: usize) -> Vec<Fr> {
+    let mut r = Fr::zero();
+    let mut c = Fr::zero();
+    for x in arr {
+        r.add_assign(x);
+        let (xl, xr) = hash(&r, &c, key);
+        r = xl;
+        c = xr;
+    }
+    let mut outputs = vec![r];
+    for _ in 1..num_outputs {
+        let (xl, xr) = hash(&r, &c, key);
+        r = xl;
+        c = xr;
+        outputs.push(r);
+    }
+    outputs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mimcsponge() {
+        let fr = |s: &str| Fr::from_str(s).unwrap();
+        let out = multi_hash(&[fr("1"), fr("2")], &Fr::zero(), 3);
+        assert_eq!(
+            out,
+            vec![
+                fr("19814528709687996974327303300007262407299502847885145507292406548098437687919"),
+                fr("21479918933254162297266020499931408698629819071798560668427831994080392652265"),
+                fr("5864304407125602198417538232776668609689728417208547813776331040141674798262"),
+            ]
+        );
+        assert_eq!(multi_hash(&[fr("1"), fr("2")], &Fr::zero(), 1), out[..1]);
+    }
+}
@@ -0,0 +1,134 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Multi-scalar multiplication, sum(scalars_i·points_i), for public points and scalars such as
+// the ones of batch verification. Neither method is constant time.
+
+use std::cmp::min;
+
+use crate::{identity_projective, test_bit, Error, Fs, Point, PointProjective};
+
+// Below this number of points Straus is cheaper than Pippenger's bucket method.
+const PIPPENGER_THRESHOLD: usize = 32;
+
+/// Computes sum(scalars_i·points_i), using Straus' method for small inputs and Pippenger's
+/// bucket method for large ones.
+pub fn multi_scalar_mul(points: &[Point], scalars: &[Fs]) -> Result<Point, Error> {
+    if points.len() != scalars.len() {
+        return Err(Error::InvalidLength {
+            expected: points.len(),
+            actual: scalars.len(),
+        });
+    }
+    let bytes: Vec<[u8; 32]> = scalars.iter().map(|s| s.to_bytes_le()).collect();
+    let r = if points.len() < PIPPENGER_THRESHOLD {
+        straus(points, &bytes)
+    } else {
+        pippenger(points, &bytes)
+    };
+    Ok(r.affine())
+}
+
+// Reads the w-bit window of a little-endian scalar starting at bit i.
+fn scalar_window(b: &[u8; 32], i: usize, w: usize) -> usize {
+    let mut digit = 0;
+    for j in (i..min(i + w, 256)).rev() {
+        digit <<= 1;
+        if test_bit(b, j) {
+            digit |= 1;
+        }
+    }
+    digit
+}
+
+fn straus(points: &[Point], scalars: &[[u8; 32]]) -> PointProjective {
+    const W: usize = 4;
+    // tables[k][j] = j·points[k]
+    let tables: Vec<Vec<PointProjective>> = points
+        .iter()
+        .map(|p| {
+            let p = p.projective();
+            let mut row = vec![identity_projective()];
+            for j in 1..1 << W {
+                let next = row[j - 1].add(&p);
+                row.push(next);
+            }
+            row
+        })
+        .collect();
+
+    let mut r = identity_projective();
+    for i in (0..256 / W).rev() {
+        for _ in 0..W {
+            r = r.add(&r);
+        }
+        for (table, b) in tables.iter().zip(scalars.iter()) {
+            let digit = scalar_window(b, i * W, W);
+            if digit != 0 {
+                r = r.add(&table[digit]);
+            }
+        }
+    }
+    r
+}
+
+fn pippenger(points: &[Point], scalars: &[[u8; 32]]) -> PointProjective {
+    // window size ~ ln(n) + 2, as in most Pippenger implementations
+    let log2 = (usize::BITS - points.len().leading_zeros()) as usize;
+    let c = log2 * 69 / 100 + 2;
+    let projectives: Vec<PointProjective> = points.iter().map(|p| p.projective()).collect();
+
+    let mut r = identity_projective();
+    let n_windows = 256usize.div_ceil(c);
+    for w in (0..n_windows).rev() {
+        for _ in 0..c {
+            r = r.add(&r);
+        }
+        let mut buckets = vec![identity_projective(); (1 << c) - 1];
+        for (p, b) in projectives.iter().zip(scalars.iter()) {
+            let digit = scalar_window(b, w * c, c);
+            if digit != 0 {
+                buckets[digit - 1] = buckets[digit - 1].add(p);
+            }
+        }
+        // sum(j·bucket_j) computed with running sums
+        let mut running = identity_projective();
+        let mut window_sum = identity_projective();
+        for bucket in buckets.iter().rev() {
+            running = running.add(bucket);
+            window_sum = window_sum.add(&running);
+        }
+        r = r.add(&window_sum);
+    }
+    r
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{mul_base, B8};
+
+    #[test]
+    fn test_multi_scalar_mul() {
+        let mut rng = rand::thread_rng();
+        for n in [0, 1, 5, PIPPENGER_THRESHOLD + 8].iter() {
+            let scalars: Vec<Fs> = (0..*n).map(|_| Fs::random(&mut rng)).collect();
+            let points: Vec<Point> = (0..*n).map(|_| mul_base(&Fs::random(&mut rng))).collect();
+            let mut expected = identity_projective();
+            for (p, s) in points.iter().zip(scalars.iter()) {
+                expected = expected.add(&p.mul_scalar(&s.to_bigint()).projective());
+            }
+            let expected = expected.affine();
+            let res = multi_scalar_mul(&points, &scalars).unwrap();
+            assert_eq!(res.x, expected.x);
+            assert_eq!(res.y, expected.y);
+        }
+        assert_eq!(
+            multi_scalar_mul(std::slice::from_ref(&*B8), &[]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 1,
+                actual: 0
+            }
+        );
+    }
+}
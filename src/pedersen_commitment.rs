@@ -0,0 +1,212 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Pedersen commitments C = v·B8 + r·H. H and the vector generators G_i are hashed to the curve
+// from fixed strings, so nobody knows their discrete logs relative to B8 or to each other, which
+// keeps the commitments binding; a uniformly random blinding r makes them perfectly hiding.
+// Commitments are additively homomorphic: C(v1, r1) + C(v2, r2) = C(v1 + v2, r1 + r2).
+
+use std::sync::Mutex;
+
+use rand::{CryptoRng, RngCore};
+use subtle::ConstantTimeEq;
+
+use crate::{decompress_point, mul_base_ct, pedersen_hash::derive_generator, Error, Fs, Point};
+
+const BLINDING_GENERATOR_PREFIX: &str = "babyjubjub-rs PedersenCommitment H";
+const VECTOR_GENERATOR_PREFIX: &str = "babyjubjub-rs PedersenCommitment G";
+
+lazy_static! {
+    static ref H: Point = derive_generator(BLINDING_GENERATOR_PREFIX, 0);
+    // vector generators derived so far
+    static ref GENERATORS: Mutex<Vec<Point>> = Mutex::new(Vec::new());
+}
+
+/// The blinding generator H.
+pub fn blinding_generator() -> Point {
+    H.clone()
+}
+
+/// The i-th generator of vector commitments.
+pub fn vector_generator(i: usize) -> Point {
+    let mut generators = GENERATORS.lock().unwrap();
+    while generators.len() <= i {
+        let p = derive_generator(VECTOR_GENERATOR_PREFIX, generators.len());
+        generators.push(p);
+    }
+    generators[i].clone()
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PedersenCommitment(Point);
+
+impl PedersenCommitment {
+    /// Commits to value with the given blinding, v·B8 + r·H.
+    pub fn commit(value: &Fs, blinding: &Fs) -> PedersenCommitment {
+        PedersenCommitment(mul_base_ct(value).add(&H.mul_scalar_ct(blinding)))
+    }
+
+    /// Commits to value with a fresh random blinding, returned alongside to open it later.
+    pub fn commit_with_rng<R: RngCore + CryptoRng>(
+        value: &Fs,
+        rng: &mut R,
+    ) -> (PedersenCommitment, Fs) {
+        let blinding = Fs::random(rng);
+        (PedersenCommitment::commit(value, &blinding), blinding)
+    }
+
+    /// Commits to values[i] under G_i, Σ v_i·G_i + r·H.
+    pub fn commit_vector(values: &[Fs], blinding: &Fs) -> PedersenCommitment {
+        let mut c = H.mul_scalar_ct(blinding);
+        for (i, v) in values.iter().enumerate() {
+            c = c.add(&vector_generator(i).mul_scalar_ct(v));
+        }
+        PedersenCommitment(c)
+    }
+
+    /// Checks that (value, blinding) opens the commitment.
+    pub fn open(&self, value: &Fs, blinding: &Fs) -> bool {
+        self.ct_eq_commitment(&PedersenCommitment::commit(value, blinding))
+    }
+
+    /// Checks that (values, blinding) opens the vector commitment.
+    pub fn open_vector(&self, values: &[Fs], blinding: &Fs) -> bool {
+        self.ct_eq_commitment(&PedersenCommitment::commit_vector(values, blinding))
+    }
+
+    fn ct_eq_commitment(&self, other: &PedersenCommitment) -> bool {
+        (self.0.compress()[..])
+            .ct_eq(&other.0.compress()[..])
+            .into()
+    }
+
+    pub fn add(&self, other: &PedersenCommitment) -> PedersenCommitment {
+        PedersenCommitment(self.0.add(&other.0))
+    }
+
+    pub fn sub(&self, other: &PedersenCommitment) -> PedersenCommitment {
+        PedersenCommitment(self.0.sub(&other.0))
+    }
+
+    pub fn point(&self) -> &Point {
+        &self.0
+    }
+
+    pub fn compress(&self) -> [u8; 32] {
+        self.0.compress()
+    }
+
+    pub fn decompress(b: &[u8; 32]) -> Result<PedersenCommitment, Error> {
+        let p = decompress_point(*b)?;
+        if !p.is_in_subgroup() {
+            return Err(Error::NotInSubgroup);
+        }
+        Ok(PedersenCommitment(p))
+    }
+}
+
+impl std::ops::Add for PedersenCommitment {
+    type Output = PedersenCommitment;
+
+    fn add(self, rhs: PedersenCommitment) -> PedersenCommitment {
+        PedersenCommitment::add(&self, &rhs)
+    }
+}
+
+impl std::ops::Sub for PedersenCommitment {
+    type Output = PedersenCommitment;
+
+    fn sub(self, rhs: PedersenCommitment) -> PedersenCommitment {
+        PedersenCommitment::sub(&self, &rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+
+    #[test]
+    fn test_generators() {
+        let h = blinding_generator();
+        assert!(h.is_in_subgroup());
+        assert!(!h.is_identity());
+        assert_ne!(h, *crate::B8);
+        let g0 = vector_generator(0);
+        let g1 = vector_generator(1);
+        assert!(g0.is_in_subgroup() && g1.is_in_subgroup());
+        assert_ne!(g0, g1);
+        assert_ne!(g0, h);
+        // distinct from the Pedersen hash generators
+        assert_ne!(g0, crate::pedersen_hash::base_point(0));
+    }
+
+    #[test]
+    fn test_commit_open() {
+        let mut rng = rand::thread_rng();
+        let v = Fs::random(&mut rng);
+        let (c, r) = PedersenCommitment::commit_with_rng(&v, &mut rng);
+        assert!(c.open(&v, &r));
+        assert!(!c.open(&r, &v));
+        let mut v2 = v;
+        v2.add_assign(&Fs::one());
+        assert!(!c.open(&v2, &r));
+
+        // hiding: same value, different blinding
+        let (c2, _) = PedersenCommitment::commit_with_rng(&v, &mut rng);
+        assert_ne!(c, c2);
+
+        let c3 = PedersenCommitment::decompress(&c.compress()).unwrap();
+        assert_eq!(c3, c);
+    }
+
+    #[test]
+    fn test_commitment_homomorphism() {
+        let mut rng = rand::thread_rng();
+        let (v1, r1) = (Fs::random(&mut rng), Fs::random(&mut rng));
+        let (v2, r2) = (Fs::random(&mut rng), Fs::random(&mut rng));
+        let c1 = PedersenCommitment::commit(&v1, &r1);
+        let c2 = PedersenCommitment::commit(&v2, &r2);
+
+        let (mut v, mut r) = (v1, r1);
+        v.add_assign(&v2);
+        r.add_assign(&r2);
+        assert!((c1.clone() + c2.clone()).open(&v, &r));
+
+        let (mut v, mut r) = (v1, r1);
+        v.sub_assign(&v2);
+        r.sub_assign(&r2);
+        assert!((c1 - c2).open(&v, &r));
+    }
+
+    #[test]
+    fn test_vector_commitment() {
+        let mut rng = rand::thread_rng();
+        let values: Vec<Fs> = (0..4).map(|_| Fs::random(&mut rng)).collect();
+        let r = Fs::random(&mut rng);
+        let c = PedersenCommitment::commit_vector(&values, &r);
+        assert!(c.open_vector(&values, &r));
+
+        let mut swapped = values.clone();
+        swapped.swap(0, 1);
+        assert!(!c.open_vector(&swapped, &r));
+        assert!(!c.open_vector(&values[..3], &r));
+
+        // componentwise homomorphic
+        let other: Vec<Fs> = (0..4).map(|_| Fs::random(&mut rng)).collect();
+        let r2 = Fs::random(&mut rng);
+        let sum: Vec<Fs> = values
+            .iter()
+            .zip(other.iter())
+            .map(|(a, b)| {
+                let mut s = *a;
+                s.add_assign(b);
+                s
+            })
+            .collect();
+        let mut r_sum = r;
+        r_sum.add_assign(&r2);
+        let c_sum = c + PedersenCommitment::commit_vector(&other, &r2);
+        assert!(c_sum.open_vector(&sum, &r_sum));
+    }
+}
@@ -0,0 +1,150 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Pedersen hash, compatible with circomlib's pedersenHash.js. The message bits are cut into
+// segments of 50 windows of 4 bits, each segment is encoded as a scalar and multiplied by its
+// own generator, derived by hashing "PedersenGenerator_<index>_<try>" with blake256 to a curve
+// point and clearing its cofactor.
+
+use std::sync::Mutex;
+
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+
+use crate::{hash_to_curve::hash_to_curve_try_and_increment, Point, SUBORDER};
+
+const GENPOINT_PREFIX: &str = "PedersenGenerator";
+const WINDOW_SIZE: usize = 4;
+const N_WINDOWS_PER_SEGMENT: usize = 50;
+
+lazy_static! {
+    // generators derived so far, as circomlib's `bases` cache
+    static ref BASES: Mutex<Vec<Point>> = Mutex::new(Vec::new());
+}
+
+/// The generator of segment point_idx, as circomlib's getBasePoint.
+pub fn base_point(point_idx: usize) -> Point {
+    let mut bases = BASES.lock().unwrap();
+    while bases.len() <= point_idx {
+        let p = derive_generator(GENPOINT_PREFIX, bases.len());
+        bases.push(p);
+    }
+    bases[point_idx].clone()
+}
+
+/// The generator "<prefix>_<index>", hashed to the curve as circomlib does for its Pedersen
+/// generators.
+pub(crate) fn derive_generator(prefix: &str, point_idx: usize) -> Point {
+    hash_to_curve_try_and_increment(prefix.as_bytes(), format!("{:032}", point_idx).as_bytes())
+}
+
+/// Pedersen hash of msg, as a point of the prime-order subgroup.
+pub fn pedersen_hash(msg: &[u8]) -> Point {
+    let bits_per_segment = WINDOW_SIZE * N_WINDOWS_PER_SEGMENT;
+    let bits: Vec<bool> = msg
+        .iter()
+        .flat_map(|b| (0..8).map(move |i| (b >> i) & 1 == 1))
+        .collect();
+    let n_segments = bits.len().div_ceil(bits_per_segment);
+
+    let mut acc_p = Point::identity();
+    for s in 0..n_segments {
+        let n_windows = if s == n_segments - 1 {
+            (bits.len() - s * bits_per_segment).div_ceil(WINDOW_SIZE)
+        } else {
+            N_WINDOWS_PER_SEGMENT
+        };
+        let mut escalar: BigInt = Zero::zero();
+        let mut exp: BigInt = One::one();
+        for w in 0..n_windows {
+            let mut o = s * bits_per_segment + w * WINDOW_SIZE;
+            let mut acc: BigInt = One::one();
+            let mut b = 0;
+            while b < WINDOW_SIZE - 1 && o < bits.len() {
+                if bits[o] {
+                    acc += BigInt::one() << b;
+                }
+                o += 1;
+                b += 1;
+            }
+            // the last bit of the window is the sign
+            if o < bits.len() && bits[o] {
+                acc = -acc;
+            }
+            escalar += acc * &exp;
+            exp <<= WINDOW_SIZE + 1;
+        }
+        if escalar < Zero::zero() {
+            escalar += &*SUBORDER;
+        }
+        acc_p = acc_p.add(&base_point(s).mul_scalar(&escalar));
+    }
+    acc_p
+}
+
+/// Packed Pedersen hash of msg, byte-for-byte the output of circomlib's pedersenHash.hash.
+pub fn hash(msg: &[u8]) -> [u8; 32] {
+    pedersen_hash(msg).compress()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_points() {
+        // BASE[0..3] of circomlib's pedersen.circom
+        let p = base_point(0);
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x171e826ad4a870fd925e0bf0e87884e70e080879c2205ef10114f28a3b6f6dd7)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x2bd407d897fbbca9f88adfd2d15252e69de8c1564eb4d3d27162e259172f1a1d)"
+        );
+        let p = base_point(1);
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x05e8290bfaba1ccfad33259a92884cc00644d5fb019ca4dcbdb50123ab32aaf1)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x05e352269c07449ea6667d7608c648894125d94e751b1b46a9cf56bbb02f3766)"
+        );
+        let p = base_point(2);
+        assert_eq!(
+            p.x.to_string(),
+            "Fr(0x0cd3df304ebdd14a89935c39a56fa9f393d590b70d6980d970f4e39cedf5d66f)"
+        );
+        assert_eq!(
+            p.y.to_string(),
+            "Fr(0x0d38cda2472cd7eeeec2a6e36d0f584d3f89f04a683dd35f6c2a82e813401278)"
+        );
+        assert!(p.is_in_subgroup());
+    }
+
+    #[test]
+    fn test_pedersen_hash() {
+        use rustc_hex::ToHex;
+
+        assert_eq!(
+            hash(&[0; 32])[..].to_hex(),
+            "37cfc3c92b8721bd82a7aa437c97cb4d7ef88399d666f72cae0d73558f867a2d"
+        );
+        assert_eq!(
+            hash(b"Hello")[..].to_hex(),
+            "0e90d7d613ab8b5ea7f4f8bc537db6bb0fa2e5e97bbac1c1f609ef9e6a35fd8b"
+        );
+        // 512 bits, three segments
+        let msg: Vec<u8> = (0..64).collect();
+        assert_eq!(
+            hash(&msg)[..].to_hex(),
+            "58b7b97eb2fd6adb8e43a6ec24ee3c27a92bae7e6375a86f426d076d4b3ed826"
+        );
+        assert!(pedersen_hash(&msg).is_in_subgroup());
+
+        // no segments at all
+        assert!(pedersen_hash(&[]).is_identity());
+    }
+}
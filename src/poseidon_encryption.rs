@@ -0,0 +1,262 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Poseidon authenticated encryption, as poseidonEncrypt / poseidonDecrypt of MACI and
+// zk-kit's poseidon-cipher, so that circuits can check decryptions. It is a duplex sponge over
+// the t = 4 Poseidon permutation: the state starts as [0, k_x, k_y, nonce + length·2^128],
+// each round permutes it and absorbs three message elements into state[1..4], which are also
+// the ciphertext. A last permutation releases state[1] as the authentication tag.
+//
+// Key derivation: MACI and zk-kit use the point formatPrivKeyForBabyJub(sk)·PK, which is
+// SharedKey::ecdh. PrivateKey::diffie_hellman clears the cofactor, so its point is 8 times
+// theirs and is not a cipher key.
+
+use ff::*;
+use num_bigint::BigInt;
+use zeroize::Zeroize;
+
+use crate::{Error, Fr, PrivateKey, PublicKey, POSEIDON};
+
+const T: usize = 4;
+const RATE: usize = T - 1;
+
+lazy_static! {
+    static ref CONSTANTS: poseidon_rs::Constants = poseidon_rs::load_constants();
+}
+
+/// Key of the cipher: the coordinates of an ECDH shared point, wiped on drop.
+pub struct SharedKey {
+    // little-endian coordinates
+    x: [u8; 32],
+    y: [u8; 32],
+}
+
+impl Drop for SharedKey {
+    fn drop(&mut self) {
+        self.x.zeroize();
+        self.y.zeroize();
+    }
+}
+
+impl std::fmt::Debug for SharedKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("SharedKey(<redacted>)")
+    }
+}
+
+impl SharedKey {
+    pub fn new(x: Fr, y: Fr) -> SharedKey {
+        let mut k = SharedKey {
+            x: [0; 32],
+            y: [0; 32],
+        };
+        x.into_repr().write_le(&mut k.x[..]).unwrap();
+        y.into_repr().write_le(&mut k.y[..]).unwrap();
+        k
+    }
+
+    /// The key of MACI's genEcdhSharedKey and zk-kit, scalar_key(sk)·pk without clearing the
+    /// cofactor, so that their circuits derive the same key from the same seed.
+    pub fn ecdh(sk: &PrivateKey, pk: &PublicKey) -> Result<SharedKey, Error> {
+        let p = pk.point().mul_scalar_ct(&sk.scalar());
+        if p.is_identity() {
+            return Err(Error::IdentityPoint);
+        }
+        Ok(SharedKey::new(p.x, p.y))
+    }
+
+    fn fields(&self) -> [Fr; 2] {
+        let fr = |b: &[u8; 32]| {
+            let mut repr = <Fr as PrimeField>::Repr::default();
+            repr.read_le(&b[..]).unwrap();
+            Fr::from_repr(repr).unwrap()
+        };
+        [fr(&self.x), fr(&self.y)]
+    }
+}
+
+// The full Poseidon permutation of POSEIDON for a state of T elements, whose first output
+// is POSEIDON.hash of the last T - 1 when the first one is zero.
+fn permute(mut state: Vec<Fr>) -> Vec<Fr> {
+    let c = &CONSTANTS.c[T - 2];
+    let m = &CONSTANTS.m[T - 2];
+    let n_rounds_f = CONSTANTS.n_rounds_f;
+    let n_rounds_p = CONSTANTS.n_rounds_p[T - 2];
+    for i in 0..(n_rounds_f + n_rounds_p) {
+        POSEIDON.ark(&mut state, c, i * T);
+        POSEIDON.sbox(n_rounds_f, n_rounds_p, &mut state, i);
+        state = POSEIDON.mix(&state, m);
+    }
+    state
+}
+
+fn initial_state(key: &SharedKey, nonce: u128, length: usize) -> Vec<Fr> {
+    let domain: BigInt = BigInt::from(nonce) + (BigInt::from(length) << 128);
+    let [k_x, k_y] = key.fields();
+    vec![
+        Fr::zero(),
+        k_x,
+        k_y,
+        Fr::from_str(&domain.to_string()).unwrap(),
+    ]
+}
+
+/// Encrypts msg, returning 3·ceil(len/3) ciphertext elements followed by the tag. A nonce must
+/// never be reused with the same key.
+pub fn poseidon_encrypt(msg: &[Fr], key: &SharedKey, nonce: u128) -> Vec<Fr> {
+    let mut state = initial_state(key, nonce, msg.len());
+    let mut ciphertext: Vec<Fr> = Vec::with_capacity(msg.len() + RATE + 1);
+    for chunk in msg.chunks(RATE) {
+        state = permute(state);
+        // a short last chunk is padded with zeros
+        for (i, m) in chunk.iter().enumerate() {
+            state[i + 1].add_assign(m);
+        }
+        ciphertext.extend_from_slice(&state[1..]);
+    }
+    state = permute(state);
+    ciphertext.push(state[1]);
+    ciphertext
+}
+
+/// Decrypts a ciphertext of a length elements message, checking the padding and the tag.
+pub fn poseidon_decrypt(
+    ciphertext: &[Fr],
+    key: &SharedKey,
+    nonce: u128,
+    length: usize,
+) -> Result<Vec<Fr>, Error> {
+    let expected = length.div_ceil(RATE) * RATE + 1;
+    if ciphertext.len() != expected {
+        return Err(Error::InvalidLength {
+            expected,
+            actual: ciphertext.len(),
+        });
+    }
+    let mut state = initial_state(key, nonce, length);
+    let mut msg: Vec<Fr> = Vec::with_capacity(expected - 1);
+    for chunk in ciphertext[..expected - 1].chunks(RATE) {
+        state = permute(state);
+        for (i, c) in chunk.iter().enumerate() {
+            let mut m = *c;
+            m.sub_assign(&state[i + 1]);
+            msg.push(m);
+            state[i + 1] = *c;
+        }
+    }
+    if msg[length..].iter().any(|m| !m.is_zero()) {
+        return Err(Error::InvalidCiphertext);
+    }
+    state = permute(state);
+    if state[1] != ciphertext[expected - 1] {
+        return Err(Error::InvalidCiphertext);
+    }
+    msg.truncate(length);
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::new_key;
+
+    fn fr(s: &str) -> Fr {
+        Fr::from_str(s).unwrap()
+    }
+
+    #[test]
+    fn test_permute() {
+        let state = permute(vec![Fr::zero(), fr("1"), fr("2"), fr("3")]);
+        assert_eq!(
+            state[0],
+            POSEIDON.hash(vec![fr("1"), fr("2"), fr("3")]).unwrap()
+        );
+    }
+
+    // No vector of the reference JavaScript implementation is vendored in this repository, these
+    // come from an independent Python port of zk-kit's poseidon-cipher (poseidonEncrypt with the
+    // circomlib Poseidon constants), whose permutation matches POSEIDON as in test_permute.
+    #[test]
+    fn test_poseidon_encrypt_known_answer() {
+        let key = SharedKey::new(fr("123"), fr("456"));
+        let ciphertext = poseidon_encrypt(&[fr("1"), fr("2")], &key, 5);
+        assert_eq!(
+            ciphertext,
+            vec![
+                fr("11149232370677644235731956321126670335324627810714740267893146172309763611035"),
+                fr("10292699523226479775360871596734297312201811137078046376887195994301040784551"),
+                fr("19963494829188122092228556994348806613003401923911886740829651346334231075725"),
+                fr("986190081649184001714251041728310015781612573693586415896877971116479586649"),
+            ]
+        );
+
+        let msg: Vec<Fr> = (1..=7).map(|i| fr(&i.to_string())).collect();
+        let ciphertext = poseidon_encrypt(&msg, &key, 5);
+        assert_eq!(ciphertext.len(), 10);
+        assert_eq!(
+            ciphertext[9],
+            fr("7683962951941958089458735418269820497640114582828578917804643343975554388681")
+        );
+        assert_eq!(poseidon_decrypt(&ciphertext, &key, 5, 7).unwrap(), msg);
+    }
+
+    #[test]
+    fn test_poseidon_encrypt_decrypt() {
+        let alice = new_key();
+        let bob = new_key();
+        let key = SharedKey::ecdh(&alice, &bob.public_key()).unwrap();
+        let key_b = SharedKey::ecdh(&bob, &alice.public_key()).unwrap();
+        assert_eq!(key.fields(), key_b.fields());
+
+        let msg: Vec<Fr> = (0..3).map(|i| fr(&(i * 1000).to_string())).collect();
+        let ciphertext = poseidon_encrypt(&msg, &key, 1);
+        assert_eq!(ciphertext.len(), 4);
+        assert_eq!(poseidon_decrypt(&ciphertext, &key_b, 1, 3).unwrap(), msg);
+
+        // wrong nonce, key or length, or a modified ciphertext
+        assert_eq!(
+            poseidon_decrypt(&ciphertext, &key_b, 2, 3).unwrap_err(),
+            Error::InvalidCiphertext
+        );
+        let other = SharedKey::new(fr("1"), fr("2"));
+        assert_eq!(
+            poseidon_decrypt(&ciphertext, &other, 1, 3).unwrap_err(),
+            Error::InvalidCiphertext
+        );
+        assert_eq!(
+            poseidon_decrypt(&ciphertext, &key_b, 1, 2).unwrap_err(),
+            Error::InvalidCiphertext
+        );
+        assert_eq!(
+            poseidon_decrypt(&ciphertext, &key_b, 1, 4).unwrap_err(),
+            Error::InvalidLength {
+                expected: 7,
+                actual: 4
+            }
+        );
+        let mut tampered = ciphertext.clone();
+        tampered[0].add_assign(&Fr::one());
+        assert_eq!(
+            poseidon_decrypt(&tampered, &key_b, 1, 3).unwrap_err(),
+            Error::InvalidCiphertext
+        );
+    }
+
+    #[test]
+    fn test_shared_key_ecdh() {
+        let alice = new_key();
+        let bob = new_key();
+        let key = SharedKey::ecdh(&alice, &bob.public_key()).unwrap();
+        let key_b = SharedKey::ecdh(&bob, &alice.public_key()).unwrap();
+        assert_eq!(key.fields(), key_b.fields());
+
+        // formatPrivKeyForBabyJub(sk)·PK, without the cofactor of diffie_hellman
+        let p = bob.public().mul_scalar(&alice.scalar_key());
+        assert_eq!(key.fields(), [p.x, p.y]);
+
+        let msg = vec![Fr::one(); 5];
+        let ciphertext = poseidon_encrypt(&msg, &key, 7);
+        assert_eq!(poseidon_decrypt(&ciphertext, &key_b, 7, 5).unwrap(), msg);
+        assert_eq!(format!("{:?}", key), "SharedKey(<redacted>)");
+    }
+}
@@ -0,0 +1,173 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+use ff::*;
+
+use num_bigint::BigInt;
+use rand::RngCore;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{utils, Error, SUBORDER};
+
+const WIDE_LIMB_BYTES: usize = 31;
+
+lazy_static! {
+    // 2^248, the weight of consecutive limbs in from_bytes_wide
+    static ref LIMB_WEIGHT: Fs = {
+        let mut b: [u8; 32] = [0; 32];
+        b[WIDE_LIMB_BYTES] = 1;
+        Fs::from_bytes_le(&b).unwrap()
+    };
+}
+
+// Scalar field of the prime-order subgroup generated by B8 (SUBORDER = ORDER >> 3).
+#[derive(PrimeField)]
+#[PrimeFieldModulus = "2736030358979909402780800718157159386076813972158567259200215660948447373041"]
+#[PrimeFieldGenerator = "31"]
+pub struct Fs(FsRepr);
+
+impl Fs {
+    /// Reduces an arbitrary (possibly negative) integer modulo SUBORDER.
+    pub fn from_bigint(n: &BigInt) -> Fs {
+        Fs::from_str(&utils::modulus(n, &SUBORDER).to_string()).unwrap()
+    }
+
+    pub fn to_bigint(&self) -> BigInt {
+        BigInt::parse_bytes(to_hex(self).as_bytes(), 16).unwrap()
+    }
+
+    /// Reduces a base field element modulo SUBORDER, as used for hash outputs.
+    pub fn from_fr(e: &crate::Fr) -> Fs {
+        Fs::from_bigint(&BigInt::parse_bytes(to_hex(e).as_bytes(), 16).unwrap())
+    }
+
+    /// Parses the canonical 32-byte little-endian encoding, rejecting values >= SUBORDER.
+    pub fn from_bytes_le(b: &[u8]) -> Result<Fs, Error> {
+        if b.len() != 32 {
+            return Err(Error::InvalidLength {
+                expected: 32,
+                actual: b.len(),
+            });
+        }
+        let mut repr = FsRepr::default();
+        repr.read_le(b).map_err(|_| Error::FieldOverflow)?;
+        Fs::from_repr(repr).map_err(|_| Error::FieldOverflow)
+    }
+
+    pub fn to_bytes_le(&self) -> [u8; 32] {
+        let mut r: [u8; 32] = [0; 32];
+        self.into_repr().write_le(&mut r[..]).unwrap();
+        r
+    }
+
+    /// Interprets any number of little-endian bytes as an integer reduced modulo SUBORDER.
+    ///
+    /// The reduction is done in Fs, as the sum of 31-byte limbs weighted by powers of 2^248,
+    /// and every intermediate is wiped, so it is suitable for secrets.
+    pub fn from_bytes_wide(b: &[u8]) -> Fs {
+        let mut acc = Fs::zero();
+        let mut weight = Fs::one();
+        for chunk in b.chunks(WIDE_LIMB_BYTES) {
+            let mut limb = Fs::from_limb_le(chunk);
+            limb.mul_assign(&weight);
+            acc.add_assign(&limb);
+            limb.zeroize();
+            weight.mul_assign(&LIMB_WEIGHT);
+        }
+        acc
+    }
+
+    // At most 31 bytes, always below SUBORDER.
+    fn from_limb_le(b: &[u8]) -> Fs {
+        let mut buf = Zeroizing::new([0u8; 32]);
+        buf[..b.len()].copy_from_slice(b);
+        let mut repr = FsRepr::default();
+        repr.read_le(&buf[..]).unwrap();
+        let r = Fs::from_repr(repr).unwrap();
+        repr.as_mut().zeroize();
+        r
+    }
+
+    /// Samples a uniformly distributed scalar, reducing 512 random bits to keep the bias negligible.
+    pub fn random<R: RngCore + ?Sized>(rng: &mut R) -> Fs {
+        let mut b: [u8; 64] = [0; 64];
+        rng.fill_bytes(&mut b);
+        Fs::from_bytes_wide(&b)
+    }
+}
+
+impl Zeroize for Fs {
+    fn zeroize(&mut self) {
+        self.0.as_mut().zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use num_bigint::Sign;
+
+    #[test]
+    fn test_fs_arithmetic() {
+        let a = Fs::from_str("12345678901234567890").unwrap();
+        let b = Fs::from_bigint(&(-BigInt::from(2)));
+        let mut c = a;
+        c.add_assign(&b);
+        assert_eq!(c, Fs::from_str("12345678901234567888").unwrap());
+
+        let mut d = a;
+        d.mul_assign(&a.inverse().unwrap());
+        assert_eq!(d, Fs::one());
+        assert!(Fs::zero().inverse().is_none());
+
+        // SUBORDER itself reduces to zero
+        assert!(Fs::from_bigint(&SUBORDER).is_zero());
+        assert_eq!(
+            Fs::from_bigint(&(&*SUBORDER + 5)).to_bigint(),
+            BigInt::from(5)
+        );
+    }
+
+    #[test]
+    fn test_fs_bytes() {
+        let a = Fs::random(&mut rand::thread_rng());
+        let b = a.to_bytes_le();
+        assert_eq!(Fs::from_bytes_le(&b).unwrap(), a);
+        assert_eq!(BigInt::from_bytes_le(Sign::Plus, &b[..]), a.to_bigint());
+
+        let (_, l) = SUBORDER.to_bytes_le();
+        assert_eq!(Fs::from_bytes_le(&l).unwrap_err(), Error::FieldOverflow);
+        assert_eq!(
+            Fs::from_bytes_le(&b[..31]).unwrap_err(),
+            Error::InvalidLength {
+                expected: 32,
+                actual: 31
+            }
+        );
+    }
+
+    #[test]
+    fn test_fs_from_bytes_wide() {
+        let mut rng = rand::thread_rng();
+        for len in [0, 1, 31, 32, 33, 62, 64, 100] {
+            let mut b = vec![0u8; len];
+            rng.fill_bytes(&mut b);
+            assert_eq!(
+                Fs::from_bytes_wide(&b),
+                Fs::from_bigint(&BigInt::from_bytes_le(Sign::Plus, &b))
+            );
+        }
+        let b = [0xFFu8; 64];
+        assert_eq!(
+            Fs::from_bytes_wide(&b),
+            Fs::from_bigint(&BigInt::from_bytes_le(Sign::Plus, &b))
+        );
+    }
+
+    #[test]
+    fn test_fs_zeroize() {
+        let mut a = Fs::random(&mut rand::thread_rng());
+        a.zeroize();
+        assert!(a.is_zero());
+    }
+}
@@ -0,0 +1,399 @@
+// BabyJubJub elliptic curve implementation in Rust.
+// For LICENSE check https://github.com/arnaucube/babyjubjub-rs
+
+// Serde support, enabled with the `serde` feature.
+//
+// Points and public keys are encoded as the hex of their 32-byte compressed form, signatures
+// as the hex of their 64-byte compressed form and private keys as the hex of their 32-byte
+// seed. The `point_decimal` module provides the circomlib encoding of points as ["x", "y"]
+// decimal strings, to be used with
+// `#[serde(with = "babyjubjub_rs::serialization::point_decimal")]`.
+//
+// EncryptedPrivateKey is the encoding of private keys at rest: scrypt derives a cipher key
+// from a password and a random salt, and the key is encrypted with poseidon_encrypt. Each
+// encryption draws a new salt, hence a new cipher key, so the nonce is always 0.
+
+use ff::*;
+use num_bigint::BigInt;
+use rand::{CryptoRng, RngCore};
+use rustc_hex::ToHex;
+use serde::de::Error as DeError;
+use serde::ser::Error as SerError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{
+    decompress_point, decompress_schnorr_signature, decompress_signature, poseidon_decrypt,
+    poseidon_encrypt, utils::decode_hex, Error, Fr, Fs, Point, PrivateKey, PublicKey,
+    SchnorrSignature, SharedKey, Signature,
+};
+
+impl Serialize for Point {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.compress()[..].to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Point {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let b = decode_hex::<32>(&s).map_err(D::Error::custom)?;
+        decompress_point(b).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for PublicKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for PublicKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        PublicKey::from_hex(&s).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Signature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.compress()[..].to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for Signature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let b = decode_hex::<64>(&s).map_err(D::Error::custom)?;
+        decompress_signature(&b).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for SchnorrSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.compress()[..].to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for SchnorrSignature {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        let b = decode_hex::<64>(&s).map_err(D::Error::custom)?;
+        decompress_schnorr_signature(&b).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for PrivateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex().map_err(S::Error::custom)?)
+    }
+}
+
+impl<'de> Deserialize<'de> for PrivateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        PrivateKey::from_hex(&s).map_err(D::Error::custom)
+    }
+}
+
+/// scrypt cost of PrivateKey::encrypt, N = 2^15 with r = 8 and p = 1 (32 MiB).
+pub const SCRYPT_DEFAULT_LOG_N: u8 = 15;
+/// Largest scrypt cost accepted when decrypting, so that a crafted file cannot ask for more
+/// than 1 GiB of memory.
+pub const SCRYPT_MAX_LOG_N: u8 = 20;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+const SALT_LEN: usize = 16;
+// seed or scalar as two 16-byte halves, then whether it is a scalar
+const PLAINTEXT_LEN: usize = 3;
+
+/// A private key encrypted under a password, see the module comment. Seed and scalar keys are
+/// both supported.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncryptedPrivateKey {
+    log_n: u8,
+    #[serde(with = "hex_bytes")]
+    salt: [u8; SALT_LEN],
+    #[serde(with = "fr_hex_vec")]
+    ciphertext: Vec<Fr>,
+}
+
+impl PrivateKey {
+    /// Encrypts the key under password with the default scrypt cost.
+    pub fn encrypt<R: RngCore + CryptoRng>(
+        &self,
+        password: &[u8],
+        rng: &mut R,
+    ) -> Result<EncryptedPrivateKey, Error> {
+        EncryptedPrivateKey::new(self, password, SCRYPT_DEFAULT_LOG_N, rng)
+    }
+}
+
+impl EncryptedPrivateKey {
+    /// Encrypts sk under password with scrypt cost N = 2^log_n.
+    pub fn new<R: RngCore + CryptoRng>(
+        sk: &PrivateKey,
+        password: &[u8],
+        log_n: u8,
+        rng: &mut R,
+    ) -> Result<EncryptedPrivateKey, Error> {
+        let mut salt: [u8; SALT_LEN] = [0; SALT_LEN];
+        rng.fill_bytes(&mut salt);
+        let key = derive_key(password, &salt, log_n)?;
+        let msg = [
+            half_to_fr(&sk.key[..16]),
+            half_to_fr(&sk.key[16..]),
+            if sk.scalar { Fr::one() } else { Fr::zero() },
+        ];
+        Ok(EncryptedPrivateKey {
+            log_n,
+            salt,
+            ciphertext: poseidon_encrypt(&msg, &key, 0),
+        })
+    }
+
+    /// Decrypts the key, failing with InvalidCiphertext on a wrong password.
+    pub fn decrypt(&self, password: &[u8]) -> Result<PrivateKey, Error> {
+        let key = derive_key(password, &self.salt, self.log_n)?;
+        let msg = poseidon_decrypt(&self.ciphertext, &key, 0, PLAINTEXT_LEN)?;
+        let mut b: [u8; 32] = [0; 32];
+        fr_to_half(&msg[0], &mut b[..16])?;
+        fr_to_half(&msg[1], &mut b[16..])?;
+        let sk = if msg[2].is_zero() {
+            Ok(PrivateKey::from_bytes(&b))
+        } else if msg[2] == Fr::one() {
+            Fs::from_bytes_le(&b).and_then(|s| PrivateKey::from_scalar(&s))
+        } else {
+            Err(Error::InvalidCiphertext)
+        };
+        b.zeroize();
+        sk
+    }
+}
+
+// scrypt output cut into two 31-byte big-endian halves, which are always below Q.
+fn derive_key(password: &[u8], salt: &[u8], log_n: u8) -> Result<SharedKey, Error> {
+    if log_n > SCRYPT_MAX_LOG_N {
+        return Err(Error::InvalidKdfParams);
+    }
+    let params =
+        scrypt::Params::new(log_n, SCRYPT_R, SCRYPT_P).map_err(|_| Error::InvalidKdfParams)?;
+    let mut out = Zeroizing::new([0u8; 62]);
+    scrypt::scrypt(password, salt, &params, &mut out[..]).map_err(|_| Error::InvalidKdfParams)?;
+    let fr_31 = |b: &[u8]| {
+        let mut buf = Zeroizing::new([0u8; 32]);
+        buf[1..].copy_from_slice(b);
+        let mut repr = <Fr as PrimeField>::Repr::default();
+        repr.read_be(&buf[..]).unwrap();
+        Fr::from_repr(repr).unwrap()
+    };
+    Ok(SharedKey::new(fr_31(&out[..31]), fr_31(&out[31..])))
+}
+
+fn half_to_fr(b: &[u8]) -> Fr {
+    let mut buf = Zeroizing::new([0u8; 32]);
+    buf[..16].copy_from_slice(b);
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    repr.read_le(&buf[..]).unwrap();
+    Fr::from_repr(repr).unwrap()
+}
+
+fn fr_to_half(e: &Fr, out: &mut [u8]) -> Result<(), Error> {
+    let mut buf = Zeroizing::new([0u8; 32]);
+    e.into_repr().write_le(&mut buf[..]).unwrap();
+    if buf[16..].iter().any(|b| *b != 0) {
+        return Err(Error::InvalidCiphertext);
+    }
+    out.copy_from_slice(&buf[..16]);
+    Ok(())
+}
+
+mod hex_bytes {
+    use super::*;
+
+    pub fn serialize<S: Serializer, const N: usize>(
+        b: &[u8; N],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&b[..].to_hex())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+        deserializer: D,
+    ) -> Result<[u8; N], D::Error> {
+        let s = String::deserialize(deserializer)?;
+        decode_hex::<N>(&s).map_err(D::Error::custom)
+    }
+}
+
+// field elements as the hex of their 32-byte big-endian encoding
+mod fr_hex_vec {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(v: &[Fr], serializer: S) -> Result<S::Ok, S::Error> {
+        v.iter()
+            .map(to_hex)
+            .collect::<Vec<String>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Fr>, D::Error> {
+        let v = <Vec<String>>::deserialize(deserializer)?;
+        v.iter()
+            .map(|s| {
+                let b = decode_hex::<32>(s).map_err(D::Error::custom)?;
+                let mut repr = <Fr as PrimeField>::Repr::default();
+                repr.read_be(&b[..]).unwrap();
+                Fr::from_repr(repr).map_err(|_| D::Error::custom(Error::FieldOverflow))
+            })
+            .collect()
+    }
+}
+
+/// Encodes a point as its ["x", "y"] decimal coordinates, as circomlib does.
+pub mod point_decimal {
+    use super::*;
+
+    fn fr_to_dec(e: &Fr) -> String {
+        BigInt::parse_bytes(to_hex(e).as_bytes(), 16)
+            .unwrap()
+            .to_string()
+    }
+
+    pub fn serialize<S: Serializer>(p: &Point, serializer: S) -> Result<S::Ok, S::Error> {
+        [fr_to_dec(&p.x), fr_to_dec(&p.y)].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Point, D::Error> {
+        let [x, y] = <[String; 2]>::deserialize(deserializer)?;
+        let x = Fr::from_str(&x).ok_or_else(|| D::Error::custom(Error::FieldOverflow))?;
+        let y = Fr::from_str(&y).ok_or_else(|| D::Error::custom(Error::FieldOverflow))?;
+        let p = Point { x, y };
+        if !p.is_on_curve() {
+            return Err(D::Error::custom(Error::NotOnCurve));
+        }
+        Ok(p)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{new_key, order_two_point};
+    use num_bigint::ToBigInt;
+
+    #[derive(Serialize, Deserialize)]
+    struct Circom {
+        #[serde(with = "point_decimal")]
+        pk: Point,
+    }
+
+    #[test]
+    fn test_point_serde() {
+        let p = new_key().public();
+        let json = serde_json::to_string(&p).unwrap();
+        assert_eq!(json, format!("\"{}\"", p.compress()[..].to_hex()));
+        let p2: Point = serde_json::from_str(&json).unwrap();
+        assert_eq!(p, p2);
+
+        assert!(serde_json::from_str::<Point>("\"00\"").is_err());
+    }
+
+    #[test]
+    fn test_point_decimal_serde() {
+        let p = Point {
+            x: Fr::from_str(
+                "17777552123799933955779906779655732241715742912184938656739573121738514868268",
+            )
+            .unwrap(),
+            y: Fr::from_str(
+                "2626589144620713026669568689430873010625803728049924121243784502389097019475",
+            )
+            .unwrap(),
+        };
+        let json = serde_json::to_string(&Circom { pk: p.clone() }).unwrap();
+        assert_eq!(
+            json,
+            "{\"pk\":[\"17777552123799933955779906779655732241715742912184938656739573121738514868268\",\"2626589144620713026669568689430873010625803728049924121243784502389097019475\"]}"
+        );
+        let c: Circom = serde_json::from_str(&json).unwrap();
+        assert_eq!(c.pk, p);
+
+        assert!(serde_json::from_str::<Circom>("{\"pk\":[\"1\",\"1\"]}").is_err());
+    }
+
+    #[test]
+    fn test_signature_private_key_serde() {
+        let sk = new_key();
+        let msg = 5.to_bigint().unwrap();
+        let sig = sk.sign(msg).unwrap();
+        let json = serde_json::to_string(&sig).unwrap();
+        assert_eq!(json.len(), 128 + 2);
+        let sig2: Signature = serde_json::from_str(&json).unwrap();
+        assert_eq!(sig2.r_b8, sig.r_b8);
+        assert_eq!(sig2.s, sig.s);
+
+        let sig = sk.sign_schnorr(5.to_bigint().unwrap()).unwrap();
+        let json = serde_json::to_string(&sig).unwrap();
+        let sig2: SchnorrSignature = serde_json::from_str(&json).unwrap();
+        assert_eq!(sig2.r, sig.r);
+        assert_eq!(sig2.s, sig.s);
+
+        let json = serde_json::to_string(&sk).unwrap();
+        let sk2: PrivateKey = serde_json::from_str(&json).unwrap();
+        assert!(
+            serde_json::to_string(&PrivateKey::from_scalar(&crate::Fs::one()).unwrap()).is_err()
+        );
+        assert_eq!(sk2.public(), sk.public());
+    }
+
+    #[test]
+    fn test_encrypted_private_key() {
+        let mut rng = rand::thread_rng();
+        let sk = new_key();
+        // a low cost keeps the test fast
+        let enc = EncryptedPrivateKey::new(&sk, b"password", 4, &mut rng).unwrap();
+        let json = serde_json::to_string(&enc).unwrap();
+        assert!(json.starts_with("{\"log_n\":4,\"salt\":\""));
+        let enc2: EncryptedPrivateKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(enc2, enc);
+        let sk2 = enc2.decrypt(b"password").unwrap();
+        assert_eq!(sk2.public(), sk.public());
+        assert!(!sk2.is_scalar());
+
+        assert_eq!(
+            enc.decrypt(b"passw0rd").unwrap_err(),
+            Error::InvalidCiphertext
+        );
+        // a new salt each time
+        assert_ne!(
+            EncryptedPrivateKey::new(&sk, b"password", 4, &mut rng).unwrap(),
+            enc
+        );
+
+        let sk_s = PrivateKey::from_scalar(&Fs::from_str("12345").unwrap()).unwrap();
+        let enc = EncryptedPrivateKey::new(&sk_s, b"", 4, &mut rng).unwrap();
+        let sk2 = enc.decrypt(b"").unwrap();
+        assert!(sk2.is_scalar());
+        assert_eq!(sk2.public(), sk_s.public());
+
+        let mut enc = enc;
+        enc.log_n = SCRYPT_MAX_LOG_N + 1;
+        assert_eq!(enc.decrypt(b"").unwrap_err(), Error::InvalidKdfParams);
+    }
+
+    #[test]
+    fn test_public_key_serde() {
+        let pk = new_key().public_key();
+        let json = serde_json::to_string(&pk).unwrap();
+        assert_eq!(json, format!("\"{}\"", pk.to_hex()));
+        let pk2: PublicKey = serde_json::from_str(&json).unwrap();
+        assert_eq!(pk2, pk);
+
+        // a point outside the subgroup decodes as a Point but not as a key
+        let t = order_two_point();
+        let p_t = format!("\"{}\"", pk.point().add(&t).compress()[..].to_hex());
+        assert!(serde_json::from_str::<Point>(&p_t).is_ok());
+        assert!(serde_json::from_str::<PublicKey>(&p_t).is_err());
+    }
+}
@@ -7,15 +7,38 @@ extern crate num_traits;
 
 use num_bigint::{BigInt, ToBigInt};
 use num_traits::{One, Zero};
+use zeroize::Zeroizing;
+
+use crate::Error;
+
+// Decodes a hex string (with or without 0x prefix) of exactly N bytes.
+pub(crate) fn decode_hex<const N: usize>(s: &str) -> Result<[u8; N], Error> {
+    use rustc_hex::FromHex;
+
+    let b: Zeroizing<Vec<u8>> = Zeroizing::new(
+        s.trim_start_matches("0x")
+            .from_hex()
+            .map_err(|_| Error::InvalidHex)?,
+    );
+    if b.len() != N {
+        return Err(Error::InvalidLength {
+            expected: N,
+            actual: b.len(),
+        });
+    }
+    let mut r = [0; N];
+    r.copy_from_slice(&b);
+    Ok(r)
+}
 
 pub fn modulus(a: &BigInt, m: &BigInt) -> BigInt {
     ((a % m) + m) % m
 }
 
-pub fn modinv(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
+pub fn modinv(a: &BigInt, q: &BigInt) -> Result<BigInt, Error> {
     let big_zero: BigInt = Zero::zero();
     if a == &big_zero {
-        return Err("no mod inv of Zero".to_string());
+        return Err(Error::NoInverse);
     }
 
     let mut mn = (q.clone(), a.clone());
@@ -110,7 +133,7 @@ pub fn concatenate_arrays<T: Clone>(x: &[T], y: &[T]) -> Vec<T> {
 }
 
 #[allow(clippy::many_single_char_names)]
-pub fn modsqrt(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
+pub fn modsqrt(a: &BigInt, q: &BigInt) -> Result<BigInt, Error> {
     // Tonelli-Shanks Algorithm (https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm)
     //
     // This implementation is following the Go lang core implementation https://golang.org/src/math/big/int.go?s=23173:23210#L859
@@ -120,7 +143,7 @@ pub fn modsqrt(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
     let zero: BigInt = Zero::zero();
     let one: BigInt = One::one();
     if legendre_symbol(&a, q) != 1 || a == &zero || q == &2.to_bigint().unwrap() {
-        return Err("not a mod p square".to_string());
+        return Err(Error::NoSquareRoot);
     } else if q % 4.to_bigint().unwrap() == 3.to_bigint().unwrap() {
         let r = a.modpow(&((q + one) / 4), &q);
         return Ok(r);
@@ -165,7 +188,7 @@ pub fn modsqrt(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
 
 #[allow(dead_code)]
 #[allow(clippy::many_single_char_names)]
-pub fn modsqrt_v2(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
+pub fn modsqrt_v2(a: &BigInt, q: &BigInt) -> Result<BigInt, Error> {
     // Tonelli-Shanks Algorithm (https://en.wikipedia.org/wiki/Tonelli%E2%80%93Shanks_algorithm)
     //
     // This implementation is following this Python implementation by Dusk https://github.com/dusk-network/dusk-zerocaf/blob/master/tools/tonelli.py
@@ -173,7 +196,7 @@ pub fn modsqrt_v2(a: &BigInt, q: &BigInt) -> Result<BigInt, String> {
     let zero: BigInt = Zero::zero();
     let one: BigInt = One::one();
     if legendre_symbol(&a, q) != 1 || a == &zero || q == &2.to_bigint().unwrap() {
-        return Err("not a mod p square".to_string());
+        return Err(Error::NoSquareRoot);
     } else if q % 4.to_bigint().unwrap() == 3.to_bigint().unwrap() {
         let r = a.modpow(&((q + one) / 4), &q);
         return Ok(r);
@@ -240,6 +263,12 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_mod_inverse_zero() {
+        let q = BigInt::parse_bytes(b"12345678", 10).unwrap();
+        assert_eq!(modinv(&Zero::zero(), &q).unwrap_err(), Error::NoInverse);
+    }
+
     #[test]
     fn test_sqrtmod() {
         let a = BigInt::parse_bytes(